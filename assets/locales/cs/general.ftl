@@ -1,8 +1,6 @@
 appearance = Vzhled
 modern = Moderní
 classic = Klasický
-update-background = Aktualizovat tapetu
-update-background-description = Stáhněte si oficiální tapetu pro launcher. Můžete jej zakázat, abyste místo toho použili svůj vlastní obrázek jako tapetu
 
 launcher-language = Jazyk launcheru
 launcher-language-description = Platí po restartu
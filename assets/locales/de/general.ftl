@@ -1,8 +1,6 @@
 appearance = Aussehen
 modern = Modern
 classic = Klassisch
-update-background = Hintergrundbild aktualisieren
-update-background-description = Lädt das offizielle Hintergrundbild für den Launcher herunter. Deaktivieren Sie dies für eine benutzerdefiniertes Hintergrundbild. 
 
 launcher-language = Launcher-Sprache
 launcher-language-description = Gilt nach Neustart
@@ -5,6 +5,8 @@ debug-file-opening-error = Failed to open debug file
 
 wish-url-search-failed = No wishes url found
 wish-url-opening-error = Could not open wishes url
+news-link-opening-error = Failed to open news link
+verify-report-export-error = Failed to export verification report
 
 wine-run-error = Failed to run {$executable} executable using wine
 
@@ -16,9 +18,13 @@ unpacking-failed = Unpacking failed
 kill-game-process-failed = Failed to kill the game's process
 
 game-file-repairing-error = Failed to repair game file
+game-files-repairing-failed = Failed to repair {$files} game files
+folders-migration-failed = Failed to migrate folders
 integrity-files-getting-error = Failed to get integrity files
 
 background-downloading-failed = Failed to download background picture
+background-processing-failed = Failed to process background picture
+launcher-settings-update-error = Failed to save launcher settings
 components-index-sync-failed = Failed to sync components index
 components-index-verify-failed = Failed to verify components index
 config-update-error = Failed to save config
@@ -1,8 +1,33 @@
 appearance = Appearance
 modern = Modern
 classic = Classic
-update-background = Update background picture
-update-background-description = Download official background picture for the launcher. You can disable it to use your custom image instead
+background-source = Background picture
+background-source-description = Where the launcher should take its background picture from
+background-source-official = Official
+background-source-keep = Keep current
+background-source-file = Local file
+background-source-folder = Local folder
+background-source-url = Custom URL
+background-official = Official picture
+background-official-description = Pictures are cached, so you can switch between them offline
+background-official-latest = Latest
+background-official-daily = Change daily
+background-official-picture = Picture {$number}
+background-file = Picture file
+background-folder = Pictures folder
+background-folder-description = Next picture is used on every launcher start
+background-url = Picture URL
+background-blur = Blur
+background-blur-description = Makes buttons and progress bar easier to read on bright pictures
+background-dim = Dim
+background-dim-description = Darkens the picture by given percent
+background-focal-point = Focal point
+background-focal-point-description = Part of the picture which stays visible when the window is resized
+background-focal-point-center = Center
+background-focal-point-top = Top
+background-focal-point-bottom = Bottom
+background-focal-point-left = Left
+background-focal-point-right = Right
 
 launcher-language = Launcher language
 launcher-language-description = Applies after restart
@@ -24,6 +49,10 @@ chinese = Chinese
 migrate-installation = Migrate installation
 migrate-installation-description = Open special window where you can change your game installation folder
 repair-game = Repair game
+verify-game = Verify files
+verify-game-tooltip = Check game files without repairing them
+force-full-check = Force full check
+force-full-check-tooltip = Check all the files, even not changed since their last verification
 
 status = Status
 
@@ -61,6 +90,32 @@ ask-superuser-permissions-description = Launcher will use them to automatically
 launcher-behavior = Launcher behavior
 launcher-behavior-description = What should launcher window do when it starts the game
 
+downloads = Downloads
+speed-limit = Speed limit
+speed-limit-description = Download speed limit in KiB/s shared by the game, voiceovers, wine, DXVK, background pictures and repaired game files downloads. Components index syncing can't be slowed down, so the following downloads wait instead. 0 disables the limit
+speed-limit-schedule = Unlimited speed schedule
+speed-limit-schedule-description = Don't limit download speed during these hours, e.g. at night
+unlimited-from = From hour
+unlimited-to = To hour
+max-jobs = Parallel downloads
+max-jobs-description = Amount of the download queue jobs running at the same time
+download-retries = Download retries
+download-retries-description = Amount of additional attempts made after a failed download. Wine and DXVK are downloaded from the other components servers when all of them fail
+retry-delay = Retry delay
+retry-delay-description = Seconds to wait before the first retry. Doubled after each failed attempt
+auto-predownload = Pre-download automatically
+auto-predownload-description = Start downloading the game update in background as soon as its pre-download is available
+update-check-interval = Update check interval
+update-check-interval-description = Minutes between the game and components updates checks while the launcher is open. 0 disables the checks
+
+notifications = Notifications
+notifications-description = Desktop notifications are sent when an operation is finished or failed while the launcher window is not focused
+notifications-downloads = Game downloads
+notifications-predownloads = Update pre-downloads
+notifications-repairs = Game files repairing
+notifications-wine = Wine installation
+notifications-prefix = Wine prefix creation
+
 wine-tools = Wine tools
 command-line = Command line
 registry-editor = Registry editor
@@ -23,6 +23,8 @@ nothing = Nothing
 save = Save
 continue = Continue
 resume = Resume
+pause = Pause
+discard = Discard
 exit = Exit
 check = Check
 restart = Restart
@@ -41,14 +43,22 @@ loading-launcher-state--patch = Loading launcher state: verifying installed patc
 
 
 checking-free-space = Checking free space
+checking-archives = Checking downloaded archives
 downloading = Downloading
 updating-permissions = Updating permissions
 unpacking = Unpacking
 verifying-files = Verifying files
+verifying-file = Verifying {$file} ({$speed}/s)
 repairing-files = Repairing files
 migrating-folders = Migrating folders
 applying-hdiff = Applying hdiff patches
 removing-outdated = Removing outdated files
+cancel = Cancel
+operation-cancelled = Operation was cancelled
+download-paused = Paused
+resume-download = Resume download?
+resume-download-description = Download of the {$version} version was interrupted
+resume-download-progress = Download of the {$version} version was interrupted after {$downloaded} of {$total}
 
 
 components-index-updated = Components index was updated
@@ -64,9 +74,43 @@ create-prefix = Create prefix
 update = Update
 download = Download
 predownload-update = Pre-download {$version} update ({$size})
+predownload-finished = {$version} update is pre-downloaded
+predownload-no-free-space = Not enough free space to pre-download {$version} update
 
 kill-game-process = Kill game process
 
+news = News
+news-announcements = Announcements
+news-events = Events
+news-info = Info
+
+queue = Queue
+queued = Queued
+queue-running = Running
+queue-install = Install {$version}
+queue-update = Update to {$version}
+queue-predownload = Pre-download {$version}
+queue-wine = Wine {$version}
+queue-component = {$component}
+queue-move-up = Move up
+queue-move-down = Move down
+
+download-finished = Download finished
+game-files-repaired = Game files are repaired
+wine-installed = Wine is installed
+wine-prefix-created = Wine prefix is created
+show-log = Show log
+
+verify-report = Verification report
+verify-report-summary = {$broken} broken files of {$checked} checked
+verify-report-game-path = Game folder: {$path}
+verify-report-cancelled = Verification was cancelled, not all the files were checked
+verify-report-missing = File is missing
+verify-report-size-mismatch = Size is {$actual} bytes instead of {$expected}
+verify-report-hash-mismatch = Hash is {$actual} instead of {$expected}
+export = Export
+repair-selected = Repair selected
+
 main-window--patch-unavailable-tooltip = Patch servers are unavailable and launcher can't verify the game's patching status. You're allowed to run the game on your own risk
 main-window--patch-outdated-tooltip = Patch is outdated or in preparation state, so unavailable for usage. Return back later to see its status
 main-window--version-outdated-tooltip = Version is too outdated and can't be updated
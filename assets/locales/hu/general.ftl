@@ -1,8 +1,6 @@
 appearance = Kinézet
 modern = Modern
 classic = Klasszikus
-update-background = Háttérkép frissítése
-update-background-description = Hivatalos launcher háttérkép letöltése. Kikapcsolással használhatsz egyedi képet helyette.
 
 launcher-language = Launcher nyelv
 launcher-language-description = Újraindítás után lép életbe
@@ -1,8 +1,6 @@
 appearance = Tampilan
 modern = Modern
 classic = Klasik
-update-background = Perbarui gambar latar belakang 
-update-background-description = Unduh gambar resmi untuk launcher. Anda bisa menonaktifkannya dan menggunakan gambar Anda sendiri
 
 launcher-language = Tampilan bahasa Launcher
 launcher-language-description = Efektif setelah muat ulang
@@ -1,8 +1,6 @@
 appearance = Aspetto
 modern = Moderno
 classic = Classico
-update-background = Aggiorna l'immagine di sfondo
-update-background-description = Scarica l'immagine di sfondo ufficiale per il launcher. Puoi disabilitarla per usare la tua immagine personalizzata
 
 launcher-language = Lingua del launcher
 launcher-language-description = Si applica dopo il riavvio dell'applicazione
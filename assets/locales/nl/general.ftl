@@ -1,8 +1,6 @@
 appearance = Uiterlijk
 modern = Modern
 classic = Klassiek
-update-background = Achtergrondafbeelding bijwerken
-update-background-description = Download de officiële achtergrondafbeelding voor de launcher. Je kunt het uitschakelen om in plaats daarvan je eigen afbeelding te gebruiken
 
 launcher-language = Launcher taal
 launcher-language-description = Past toe na opnieuw opstarten
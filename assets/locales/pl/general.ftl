@@ -1,8 +1,6 @@
 appearance = Wygląd
 modern = Nowoczesny
 classic = Klasyczny
-update-background = Zaktualizuj obraz tła
-update-background-description = Pobierz oficjalny obraz tła dla launchera. Możesz go wyłączyć, aby użyć własnego obrazu.
 
 launcher-language = Język launchera
 launcher-language-description = Zostanie zastosowany po ponownym uruchomieniu
@@ -1,8 +1,6 @@
 appearance = Aparência
 modern = Moderna
 classic = Classica
-update-background = Atualizar imagem de fundo
-update-background-description = Baixar foto oficial de fundo para o launcher. Pode desativar para usar sua própria imagem personalizada
 
 launcher-language = Idioma do launcher
 launcher-language-description = Aplicado após reiniciar
@@ -1,8 +1,6 @@
 appearance = Utseende
 modern = Modern
 classic = Klassisk
-update-background = Uppdatera bakgrundsbild
-update-background-description = Ladda ner officiell bakgrundsbild för startprogrammet. Du kan inaktivera den för att använda din egna anpassade bild istället
 
 launcher-language = Språk för starthanterare
 launcher-language-description = Appliceras efter omstart
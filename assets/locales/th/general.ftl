@@ -1,8 +1,6 @@
 appearance = รูปร่าง
 modern = โมเดิร์น
 classic = คลาสสิค
-update-background = อัพเดตภาพพื้นหลัง
-update-background-description = ดาวน์โหลดภาพพื้นหลังสำหรับ launcher คุณสามารถปิดการใช้งานเพื่อใช้รูปภาพที่คุณกำหนดเองแทนได้
 
 launcher-language = ภาษาของ launcher
 launcher-language-description = ใช้หลังจากรีสตาร์ท launcher
@@ -1,8 +1,6 @@
 appearance = Görünüş
 modern = Modern
 classic = Klasik
-update-background = Arkaplan imajını güncelle
-update-background-description = İstemci için resmi arka plan resmini indirin. Bunun yerine özel resminizi kullanmak için devre dışı bırakabilirsiniz.
 
 launcher-language = İstemci dili
 launcher-language-description = Yeniden başlattıktan sonra uygulanır
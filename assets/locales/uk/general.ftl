@@ -1,8 +1,6 @@
 appearance = Зовнішній вигляд
 modern = Сучасний
 classic = Класичний
-update-background = Оновлювати фонове зображення
-update-background-description = Завантажувати фонове зображення для лаунчера. Ви можете вимкнути це, щоб використовувати власне зображення
 
 launcher-language = Мова лаунчера
 launcher-language-description = Застосовується після перезапуску
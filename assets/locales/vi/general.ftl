@@ -1,8 +1,6 @@
 appearance = Ngoại hình
 modern = Hiện đại
 classic = Cổ điển
-update-background = Cập nhật ảnh nền
-update-background-description = Tải xuống ảnh nền chính thức cho Launcher. Nhưng bạn cũng có thể tắt nó để sử dụng hình ảnh của mình
 launcher-language = Ngôn ngữ Launcher
 launcher-language-description = Áp dụng sau khi khởi động lại
 
@@ -0,0 +1,261 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use anime_launcher_sdk::anime_game_core::minreq;
+
+use anime_launcher_sdk::config::ConfigExt;
+use anime_launcher_sdk::zzz::config::Config;
+
+use anime_launcher_sdk::anime_game_core::zzz::consts::GameEdition;
+
+use crate::settings::Settings;
+
+/// Time during which the cached API response is used without requesting the API again
+const CACHE_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// Time after which the API request is failed and the cached response is used
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Get launcher API base URL for the game edition
+pub fn get_api_base(edition: GameEdition) -> &'static str {
+    match edition {
+        GameEdition::Global => concat!("https://sg-hyp-api.", "ho", "yo", "verse", ".com"),
+        GameEdition::China  => concat!("https://hyp-api.", "mi", "ho", "yo", ".com")
+    }
+}
+
+/// Get launcher id used by the API of the game edition
+pub fn get_launcher_id(edition: GameEdition) -> &'static str {
+    match edition {
+        GameEdition::Global => "VYTpXlbWo8",
+        GameEdition::China  => "jGHBHlcOq1"
+    }
+}
+
+/// Get launcher API method uri
+///
+/// Server is chosen by the game edition, or taken from the `api_url` launcher setting
+pub fn get_uri(method: &str, params: &[(&str, &str)]) -> anyhow::Result<String> {
+    let edition = Config::get()
+        .map(|config| config.launcher.edition)
+        .unwrap_or(crate::CONFIG.launcher.edition);
+
+    let base = Settings::get_or_default().api_url
+        .unwrap_or_else(|| get_api_base(edition).to_owned());
+
+    let language = crate::i18n::format_lang(crate::i18n::get_lang());
+
+    let mut query = vec![
+        ("launcher_id", get_launcher_id(edition)),
+        ("language", language.as_str())
+    ];
+
+    query.extend_from_slice(params);
+
+    build_uri(&base, method, &query)
+}
+
+/// Append API method path and percent-encoded query params to the base URL
+fn build_uri(base: &str, method: &str, params: &[(&str, &str)]) -> anyhow::Result<String> {
+    let mut uri = url::Url::parse(base)?;
+
+    uri.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Launcher API URL can't have a path: {base}"))?
+        .pop_if_empty()
+        .extend(["hyp", "hyp-connect", "api", method]);
+
+    uri.query_pairs_mut()
+        .extend_pairs(params);
+
+    Ok(uri.into())
+}
+
+/// Cached API response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ApiCache {
+    uri: String,
+
+    /// UNIX timestamp of the last successful request
+    fetched_at: u64,
+
+    etag: Option<String>,
+    last_modified: Option<String>,
+
+    response: serde_json::Value
+}
+
+impl ApiCache {
+    fn load(method: &str) -> Option<Self> {
+        let cache = std::fs::read(crate::API_CACHE_FOLDER.join(format!("{method}.json"))).ok()?;
+
+        serde_json::from_slice(&cache).ok()
+    }
+
+    fn save(&self, method: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(crate::API_CACHE_FOLDER.as_path())?;
+
+        std::fs::write(crate::API_CACHE_FOLDER.join(format!("{method}.json")), serde_json::to_vec(self)?)?;
+
+        Ok(())
+    }
+}
+
+/// Request API method or use its cached response if it's still fresh or the API is unreachable
+pub fn request(method: &str, params: &[(&str, &str)]) -> anyhow::Result<serde_json::Value> {
+    let uri = get_uri(method, params)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    let cache = ApiCache::load(method);
+
+    // Conditional request headers are only valid for the same uri
+    let same_uri_cache = cache.as_ref().filter(|cache| cache.uri == uri);
+
+    if let Some(cache) = same_uri_cache {
+        if now.saturating_sub(cache.fetched_at) < CACHE_LIFETIME.as_secs() {
+            tracing::debug!("Using cached {method} API response");
+
+            return Ok(cache.response.clone());
+        }
+    }
+
+    let mut request = minreq::get(&uri)
+        .with_timeout(REQUEST_TIMEOUT.as_secs());
+
+    if let Some(cache) = same_uri_cache {
+        if let Some(etag) = &cache.etag {
+            request = request.with_header("If-None-Match", etag);
+        }
+
+        if let Some(last_modified) = &cache.last_modified {
+            request = request.with_header("If-Modified-Since", last_modified);
+        }
+    }
+
+    let response = match request.send() {
+        Ok(response) if response.status_code == 304 || (200..300).contains(&response.status_code) => response,
+
+        Ok(response) => {
+            let err = anyhow::anyhow!("{method} API returned {} status code", response.status_code);
+
+            return offline_response(method, cache, err);
+        }
+
+        Err(err) => return offline_response(method, cache, err.into())
+    };
+
+    let (etag, last_modified, body) = match same_uri_cache {
+        Some(cache) if response.status_code == 304 => {
+            tracing::debug!("{method} API response is not modified");
+
+            (cache.etag.clone(), cache.last_modified.clone(), cache.response.clone())
+        }
+
+        _ => (
+            response.headers.get("etag").cloned(),
+            response.headers.get("last-modified").cloned(),
+            serde_json::from_slice(response.as_bytes())?
+        )
+    };
+
+    let cache = ApiCache {
+        uri,
+        fetched_at: now,
+        etag,
+        last_modified,
+        response: body
+    };
+
+    if let Err(err) = cache.save(method) {
+        tracing::error!("Failed to cache {method} API response: {err}");
+    }
+
+    Ok(cache.response)
+}
+
+fn offline_response(method: &str, cache: Option<ApiCache>, err: anyhow::Error) -> anyhow::Result<serde_json::Value> {
+    match cache {
+        Some(cache) => {
+            tracing::warn!("Failed to request {method} API, using its cached response: {err}");
+
+            Ok(cache.response)
+        }
+
+        None => Err(err)
+    }
+}
+
+/// Get basic info about the game: its id, backgrounds, etc.
+pub fn get_game_basic_info() -> anyhow::Result<serde_json::Value> {
+    let json = request("getAllGameBasicInfo", &[])?;
+
+    json["data"]["game_info_list"].as_array()
+        .ok_or_else(|| anyhow::anyhow!("Failed to list games in the launcher API"))?
+        .iter()
+        .find(|game| {
+            match game["game"]["biz"].as_str() {
+                Some(biz) => biz.starts_with("nap_"),
+                _ => false
+            }
+        })
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Failed to find the game in the launcher API"))
+}
+
+/// Get the game's content: banners, posts, etc.
+pub fn get_game_content() -> anyhow::Result<serde_json::Value> {
+    let info = get_game_basic_info()?;
+
+    let game_id = info["game"]["id"].as_str()
+        .ok_or_else(|| anyhow::anyhow!("Failed to get the game id from the launcher API"))?;
+
+    let json = request("getGameContent", &[("game_id", game_id)])?;
+
+    Ok(json["data"]["content"].clone())
+}
+
+/// Get the game's packages: archives of the latest version, patches and pre-downloads
+pub fn get_game_packages() -> anyhow::Result<serde_json::Value> {
+    let info = get_game_basic_info()?;
+
+    let game_id = info["game"]["id"].as_str()
+        .ok_or_else(|| anyhow::anyhow!("Failed to get the game id from the launcher API"))?;
+
+    let json = request("getGamePackages", &[("game_ids[]", game_id)])?;
+
+    json["data"]["game_packages"].as_array()
+        .and_then(|packages| packages.first())
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Failed to find the game packages in the launcher API"))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn build_uri() {
+        assert_eq!(
+            super::build_uri("https://example.com", "getGames", &[("launcher_id", "abc")]).unwrap(),
+            "https://example.com/hyp/hyp-connect/api/getGames?launcher_id=abc"
+        );
+
+        // Local mirror
+        assert_eq!(
+            super::build_uri("http://localhost:8080/mirror/", "getGames", &[("launcher_id", "abc")]).unwrap(),
+            "http://localhost:8080/mirror/hyp/hyp-connect/api/getGames?launcher_id=abc"
+        );
+    }
+
+    #[test]
+    fn build_uri_encodes_params() {
+        assert_eq!(
+            super::build_uri("https://example.com", "getGamePackages", &[("game_ids[]", "a&b=c"), ("language", "en us")]).unwrap(),
+            "https://example.com/hyp/hyp-connect/api/getGamePackages?game_ids%5B%5D=a%26b%3Dc&language=en+us"
+        );
+    }
+
+    #[test]
+    fn build_uri_wrong_base() {
+        assert!(super::build_uri("example.com", "getGames", &[]).is_err());
+        assert!(super::build_uri("mailto:user@example.com", "getGames", &[]).is_err());
+    }
+}
@@ -1,111 +1,395 @@
-use std::process::Command;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
 
 use anime_launcher_sdk::anime_game_core::installer::downloader::Downloader;
-use anime_launcher_sdk::anime_game_core::minreq;
 
 use md5::{Md5, Digest};
 
+use image::{ImageReader, ImageFormat};
+
+use crate::settings::{Settings, BackgroundSource, OfficialBackground};
+use crate::bandwidth::Throttle;
+
+/// Extensions of the pictures which can be used as a background
+pub const PICTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Prevents different threads from writing the processed picture at the same time
+static PROCESSING_LOCK: Mutex<()> = Mutex::new(());
+
 #[derive(Debug, Clone)]
 pub struct Background {
     pub uri: String,
     pub hash: String
 }
 
-pub fn get_uri() -> String {
-    let lang = crate::i18n::get_lang();
+impl Background {
+    /// Path to the cached picture
+    pub fn path(&self) -> PathBuf {
+        crate::BACKGROUNDS_CACHE_FOLDER.join(&self.hash)
+    }
+
+    /// Check if the picture is cached and isn't corrupted
+    pub fn is_downloaded(&self) -> anyhow::Result<bool> {
+        let path = self.path();
+
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        let hash = Md5::digest(std::fs::read(path)?);
 
-    if lang.language == unic_langid::langid!("zh-cn").language {
-        concat!("https://hyp-api.", "mi", "ho", "yo", ".com/hyp/hyp-connect/api/getAllGameBasicInfo?launcher_id=jGHBHlcOq1").to_owned()
+        Ok(format!("{:x}", hash).to_lowercase() == self.hash)
     }
 
-    else {
-        let uri = concat!("https://sg-hyp-api.", "ho", "yo", "verse", ".com/hyp/hyp-connect/api/getAllGameBasicInfo?launcher_id=VYTpXlbWo8&language=");
+    /// Download the picture to the cache if it's not already there
+    pub fn download(&self) -> anyhow::Result<PathBuf> {
+        let path = self.path();
+
+        if self.is_downloaded()? {
+            tracing::debug!("Background picture {} is already downloaded. Skipping", self.hash);
+
+            return Ok(path);
+        }
+
+        std::fs::create_dir_all(crate::BACKGROUNDS_CACHE_FOLDER.as_path())?;
 
-        uri.to_owned() + &crate::i18n::format_lang(lang)
+        let mut downloader = Downloader::new(&self.uri)?;
+
+        downloader.continue_downloading = false;
+
+        let throttle = Throttle::new();
+
+        if let Err(err) = downloader.download(&path, move |curr, _| throttle.update(curr)) {
+            anyhow::bail!(err);
+        }
+
+        Ok(path)
     }
 }
 
+/// Get all the official background pictures of the game
 #[cached::proc_macro::cached(result)]
-pub fn get_background_info() -> anyhow::Result<Background> {
-    let json = serde_json::from_slice::<serde_json::Value>(minreq::get(get_uri()).send()?.as_bytes())?;
+pub fn get_backgrounds_info() -> anyhow::Result<Vec<Background>> {
+    let info = crate::api::get_game_basic_info()?;
 
-    let uri = json["data"]["game_info_list"].as_array()
-        .ok_or_else(|| anyhow::anyhow!("Failed to list games in the backgrounds API"))?
+    let backgrounds = info["backgrounds"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Failed to get background pictures list"))?
         .iter()
-        .find(|game| {
-            match game["game"]["biz"].as_str() {
-                Some(biz) => biz.starts_with("nap_"),
-                _ => false
+        .filter_map(|background| background["background"]["url"].as_str())
+        .map(|uri| {
+            let hash = uri.split('/')
+                .last()
+                .unwrap_or_default()
+                .split('_')
+                .next()
+                .unwrap_or_default()
+                .to_owned();
+
+            Background {
+                uri: uri.to_string(),
+                hash
             }
         })
-        .ok_or_else(|| anyhow::anyhow!("Failed to find the game in the backgrounds API"))?["backgrounds"]
-        .as_array()
-        .and_then(|backgrounds| backgrounds.iter().next())
-        .and_then(|background| background["background"]["url"].as_str())
-        .ok_or_else(|| anyhow::anyhow!("Failed to get background picture url"))?
-        .to_string();
-
-    let hash = uri.split('/')
-        .last()
-        .unwrap_or_default()
-        .split('_')
-        .next()
-        .unwrap_or_default()
-        .to_owned();
-
-    Ok(Background {
-        uri,
-        hash
-    })
+        .collect::<Vec<_>>();
+
+    if backgrounds.is_empty() {
+        anyhow::bail!("Failed to get background picture url");
+    }
+
+    Ok(backgrounds)
 }
 
+/// Update background picture using the source chosen in the launcher settings
 pub fn download_background() -> anyhow::Result<()> {
+    let settings = Settings::get()?;
+
+    tracing::debug!("Updating background picture from {:?} source", settings.background.source);
+
+    match settings.background.source {
+        BackgroundSource::Official => download_official_background(&settings.background.official),
+
+        BackgroundSource::Keep => Ok(()),
+
+        BackgroundSource::File => {
+            let Some(path) = &settings.background.file else {
+                anyhow::bail!("Background picture is not selected");
+            };
+
+            process_background(path)
+        }
+
+        BackgroundSource::Folder => {
+            let Some(folder) = &settings.background.folder else {
+                anyhow::bail!("Background pictures folder is not selected");
+            };
+
+            let picture = next_folder_picture(folder, settings.background.folder_current.as_deref())?;
+
+            process_background(&picture)?;
+
+            Settings::update(|settings| settings.background.folder_current = Some(picture))?;
+
+            Ok(())
+        }
+
+        BackgroundSource::Url => {
+            let url = settings.background.url.trim();
+
+            if url.is_empty() {
+                anyhow::bail!("Background picture URL is not specified");
+            }
+
+            let mut downloader = Downloader::new(url)?;
+
+            downloader.continue_downloading = false;
+
+            let throttle = Throttle::new();
+
+            if let Err(err) = downloader.download(crate::BACKGROUND_FILE.as_path(), move |curr, _| throttle.update(curr)) {
+                return keep_processed_background(anyhow::anyhow!(err));
+            }
+
+            process_background(crate::BACKGROUND_FILE.as_path())
+        }
+    }
+}
+
+fn download_official_background(picture: &OfficialBackground) -> anyhow::Result<()> {
     tracing::debug!("Downloading background picture");
 
-    let info = get_background_info()?;
+    let backgrounds = match get_backgrounds_info() {
+        Ok(backgrounds) => backgrounds,
+        Err(err) => return keep_processed_background(err)
+    };
 
-    let mut download_image = true;
+    let background = match picture {
+        OfficialBackground::Latest => &backgrounds[0],
 
-    if crate::BACKGROUND_FILE.exists() {
-        let hash = Md5::digest(std::fs::read(crate::BACKGROUND_FILE.as_path())?);
+        OfficialBackground::Daily => {
+            let day = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / (24 * 60 * 60);
+
+            &backgrounds[day as usize % backgrounds.len()]
+        }
 
-        if format!("{:x}", hash).to_lowercase() == info.hash {
-            tracing::debug!("Background picture is already downloaded. Skipping");
+        OfficialBackground::Picture(hash) => backgrounds.iter()
+            .find(|background| &background.hash == hash)
+            .unwrap_or(&backgrounds[0])
+    };
 
-            download_image = false;
+    let path = match background.download() {
+        Ok(path) => path,
+        Err(err) => return keep_processed_background(err)
+    };
+
+    process_background(&path)?;
+
+    // Cache other pictures in background so they can be chosen later
+    std::thread::spawn(move || {
+        if let Err(err) = cache_backgrounds(&backgrounds) {
+            tracing::error!("Failed to cache background pictures: {err}");
         }
+    });
+
+    Ok(())
+}
+
+/// Keep showing the last processed picture if a new one can't be downloaded, e.g. when offline.
+/// The error is returned only if there's no picture to show
+fn keep_processed_background(err: anyhow::Error) -> anyhow::Result<()> {
+    if !crate::PROCESSED_BACKGROUND_FILE.exists() {
+        return Err(err);
     }
 
-    if download_image {
-        let mut downloader = Downloader::new(&info.uri)?;
+    tracing::warn!("Failed to download background picture, using the last processed one: {err}");
 
-        downloader.continue_downloading = false;
+    Ok(())
+}
 
-        if let Err(err) = downloader.download(crate::BACKGROUND_FILE.as_path(), |_, _| {}) {
-            anyhow::bail!(err);
+/// Download all the pictures and remove ones which are not used by the game anymore
+fn cache_backgrounds(backgrounds: &[Background]) -> anyhow::Result<()> {
+    for background in backgrounds {
+        if !background.path().exists() {
+            background.download()?;
         }
     }
 
-    // Workaround for GTK weakness
-    if info.uri.ends_with(".webp") {
-        Command::new("dwebp")
-            .arg(crate::BACKGROUND_FILE.as_path())
-            .arg("-o")
-            .arg(crate::PROCESSED_BACKGROUND_FILE.as_path())
-            .spawn()?
-            .wait()?;
-
-        // If it failed to re-code the file - just copy it
-        // Will happen with HSR because devs apparently named
-        // their background image ".webp" while it's JPEG
-        if !crate::PROCESSED_BACKGROUND_FILE.exists() {
-            std::fs::copy(crate::BACKGROUND_FILE.as_path(), crate::PROCESSED_BACKGROUND_FILE.as_path())?;
+    for entry in std::fs::read_dir(crate::BACKGROUNDS_CACHE_FOLDER.as_path())?.flatten() {
+        let name = entry.file_name();
+
+        if !backgrounds.iter().any(|background| name == background.hash.as_str()) {
+            std::fs::remove_file(entry.path())?;
         }
     }
 
-    else {
-        std::fs::copy(crate::BACKGROUND_FILE.as_path(), crate::PROCESSED_BACKGROUND_FILE.as_path())?;
+    Ok(())
+}
+
+/// Source picture and options used to make the processed background picture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ProcessedBackground {
+    source: PathBuf,
+
+    /// md5 hash of the source picture
+    hash: String,
+
+    blur: u32,
+    dim: u32
+}
+
+impl ProcessedBackground {
+    fn load() -> Option<Self> {
+        let info = std::fs::read(crate::PROCESSED_BACKGROUND_INFO_FILE.as_path()).ok()?;
+
+        serde_json::from_slice(&info).ok()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        std::fs::write(crate::PROCESSED_BACKGROUND_INFO_FILE.as_path(), serde_json::to_vec(self)?)?;
+
+        Ok(())
+    }
+}
+
+/// Decode the picture, apply blur and dim from the launcher settings
+/// and write it to the `PROCESSED_BACKGROUND_FILE` as PNG
+/// 
+/// Does nothing if the picture was already processed with the same options
+fn process_background(path: &Path) -> anyhow::Result<()> {
+    let _lock = PROCESSING_LOCK.lock().unwrap();
+
+    let settings = Settings::get_or_default().background;
+
+    let source = std::fs::read(path)
+        .map_err(|err| anyhow::anyhow!("Failed to open background picture {}: {err}", path.to_string_lossy()))?;
+
+    let info = ProcessedBackground {
+        source: path.to_path_buf(),
+        hash: format!("{:x}", Md5::digest(&source)).to_lowercase(),
+        blur: settings.blur,
+        dim: settings.dim.min(100)
+    };
+
+    if crate::PROCESSED_BACKGROUND_FILE.exists() && ProcessedBackground::load().as_ref() == Some(&info) {
+        tracing::debug!("Background picture is already processed. Skipping");
+
+        return Ok(());
+    }
+
+    // Format is guessed from the file's content because
+    // pictures can have wrong extensions. E.g. HSR devs apparently
+    // named their background image ".webp" while it's JPEG
+    let mut picture = ImageReader::new(Cursor::new(source))
+        .with_guessed_format()?
+        .decode()
+        .map_err(|err| anyhow::anyhow!("Failed to decode background picture {}: {err}", path.to_string_lossy()))?;
+
+    if info.blur > 0 {
+        picture = picture.blur(info.blur as f32);
+    }
+
+    let mut picture = picture.into_rgb8();
+
+    if info.dim > 0 {
+        let brightness = 100 - info.dim;
+
+        for pixel in picture.pixels_mut() {
+            for channel in pixel.0.iter_mut() {
+                *channel = (*channel as u32 * brightness / 100) as u8;
+            }
+        }
+    }
+
+    picture.save_with_format(crate::PROCESSED_BACKGROUND_FILE.as_path(), ImageFormat::Png)?;
+
+    if let Err(err) = info.save() {
+        tracing::error!("Failed to save processed background picture info: {err}");
     }
 
     Ok(())
 }
+
+/// Get width and height of the processed background picture
+pub fn processed_background_size() -> anyhow::Result<(u32, u32)> {
+    let size = ImageReader::open(crate::PROCESSED_BACKGROUND_FILE.as_path())?
+        .with_guessed_format()?
+        .into_dimensions()?;
+
+    Ok(size)
+}
+
+/// Process the last used source picture again, e.g. when processing options were changed
+pub fn reprocess_background() -> anyhow::Result<()> {
+    let Some(info) = ProcessedBackground::load() else {
+        anyhow::bail!("Background picture was not processed yet");
+    };
+
+    process_background(&info.source)
+}
+
+/// Get the picture which goes after the `current` one in the folder
+fn next_folder_picture(folder: &Path, current: Option<&Path>) -> anyhow::Result<PathBuf> {
+    let mut pictures = std::fs::read_dir(folder)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| PICTURE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+
+    pictures.sort();
+
+    let next = current
+        .and_then(|current| pictures.iter().position(|picture| picture == current))
+        .map(|position| (position + 1) % pictures.len())
+        .unwrap_or(0);
+
+    pictures.into_iter()
+        .nth(next)
+        .ok_or_else(|| anyhow::anyhow!("No pictures found in the folder: {}", folder.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_picture() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+
+        for file in ["b.PNG", "a.jpg", "c.webp", "notes.txt", "d"] {
+            std::fs::write(folder.join(file), b"").unwrap();
+        }
+
+        let next = |current: &str| next_folder_picture(folder, Some(&folder.join(current))).unwrap();
+
+        assert_eq!(next_folder_picture(folder, None).unwrap(), folder.join("a.jpg"));
+
+        assert_eq!(next("a.jpg"), folder.join("b.PNG"));
+        assert_eq!(next("b.PNG"), folder.join("c.webp"));
+        assert_eq!(next("c.webp"), folder.join("a.jpg"));
+
+        // Removed or not a picture
+        assert_eq!(next("removed.png"), folder.join("a.jpg"));
+        assert_eq!(next("notes.txt"), folder.join("a.jpg"));
+    }
+
+    #[test]
+    fn no_pictures() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+
+        std::fs::write(folder.join("notes.txt"), b"").unwrap();
+
+        assert!(next_folder_picture(folder, None).is_err());
+        assert!(next_folder_picture(&folder.join("missing"), None).is_err());
+    }
+}
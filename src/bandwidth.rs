@@ -0,0 +1,143 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::settings::{Settings, DownloadSettings};
+
+/// Period after which downloaded bytes are counted from scratch
+const WINDOW: Duration = Duration::from_secs(2);
+
+/// Period after which the local hour is requested again
+const HOUR_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: RwLock<DownloadSettings> = RwLock::new(Settings::get_or_default().downloads);
+
+    /// (window start, bytes downloaded since the window start) of all the downloads
+    static ref BUCKET: Mutex<(Instant, u64)> = Mutex::new((Instant::now(), 0));
+
+    /// (check time, local hour) to not request the local time on each downloaded chunk
+    static ref HOUR: Mutex<Option<(Instant, u32)>> = Mutex::new(None);
+}
+
+/// Apply updated download settings to the running downloads
+pub fn set_settings(settings: DownloadSettings) {
+    *SETTINGS.write().unwrap() = settings;
+    *BUCKET.lock().unwrap() = (Instant::now(), 0);
+}
+
+/// Get current download speed limit in bytes per second
+pub fn current_limit() -> Option<u64> {
+    SETTINGS.read().unwrap().speed_limit_at(local_hour())
+}
+
+fn local_hour() -> u32 {
+    let mut cached = HOUR.lock().unwrap();
+
+    match *cached {
+        Some((checked, hour)) if checked.elapsed() < HOUR_CHECK_INTERVAL => hour,
+
+        _ => {
+            let hour = gtk::glib::DateTime::now_local()
+                .map(|time| time.hour() as u32)
+                .unwrap_or_default();
+
+            *cached = Some((Instant::now(), hour));
+
+            hour
+        }
+    }
+}
+
+/// Count downloaded bytes and wait if the speed limit is exceeded
+fn consume(bytes: u64) {
+    let mut bucket = BUCKET.lock().unwrap();
+
+    let Some(limit) = current_limit() else {
+        *bucket = (Instant::now(), 0);
+
+        return;
+    };
+
+    let (start, downloaded) = &mut *bucket;
+
+    *downloaded += bytes;
+
+    let expected = Duration::from_secs_f64(*downloaded as f64 / limit as f64);
+    let elapsed = start.elapsed();
+
+    // Don't let the time without downloads to be spent later at the full speed
+    if elapsed >= WINDOW && expected <= elapsed {
+        *bucket = (Instant::now(), 0);
+
+        return;
+    }
+
+    drop(bucket);
+
+    std::thread::sleep(expected.saturating_sub(elapsed));
+}
+
+/// Run an operation which downloads files to the folder by itself
+/// and count the folder size growth against the speed limit
+///
+/// Such operations (e.g. components index syncing) can't be slowed down,
+/// so the calling thread and other downloads wait afterwards instead
+pub fn count_folder<T>(folder: impl AsRef<Path>, operation: impl FnOnce() -> T) -> T {
+    let folder = folder.as_ref();
+    let before = folder_size(folder);
+
+    let result = operation();
+
+    consume(folder_size(folder).saturating_sub(before));
+
+    result
+}
+
+fn folder_size(path: &Path) -> u64 {
+    let Ok(entries) = path.read_dir() else {
+        return 0;
+    };
+
+    entries.flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => folder_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|metadata| metadata.len()).unwrap_or_default(),
+            _ => 0
+        })
+        .sum()
+}
+
+/// Limits speed of a single download by the global limit
+///
+/// Supposed to be updated from the downloader progress callbacks,
+/// which are called by the downloaders after each downloaded chunk
+#[derive(Debug, Clone)]
+pub struct Throttle(Arc<AtomicU64>);
+
+impl Default for Throttle {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Throttle {
+    #[inline]
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicU64::new(u64::MAX)))
+    }
+
+    /// Update amount of downloaded bytes and wait if the speed limit is exceeded
+    pub fn update(&self, downloaded: u64) {
+        let last = self.0.swap(downloaded, Ordering::Relaxed);
+
+        // First update of the continued download or new file
+        if last == u64::MAX || downloaded < last {
+            return;
+        }
+
+        consume(downloaded - last);
+    }
+}
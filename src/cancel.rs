@@ -0,0 +1,253 @@
+//! Cancellation of the long-running operations
+//!
+//! Our own operations (verification, repairing, migration) check `CancellationToken::is_cancelled`
+//! between their steps and stop by themselves.
+//!
+//! SDK downloads (`install_to`, `download_to`, `Installer::install`) can't be stopped this way:
+//! their progress callbacks return nothing and SDK 1.22 has no other way to interrupt them.
+//! Instead they're started by `run_cancellable` in a separate worker thread, which is abandoned
+//! when the operation is cancelled:
+//!
+//! - `CancellationToken::stop_if_cancelled` is supposed to be called from the progress callbacks
+//!   while downloading. It never returns if the operation was cancelled, so the worker doesn't
+//!   continue the download and never starts unpacking
+//! - `run_cancellable` returns `Err(Cancelled)` as soon as the worker is stopped. Partially
+//!   downloaded archive is kept in the temp folder to continue the download later
+//! - outside of `run_cancellable` the callbacks are never stopped, so the operation is finished
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+/// Interval between two checks of the stopped worker
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared flag used to ask a long-running operation to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+
+    /// Operation is stopped to be resumed later
+    paused: Arc<AtomicBool>,
+
+    /// Operation is running by `run_cancellable`
+    running: Arc<AtomicBool>,
+
+    /// Worker of the operation was stopped by `stop_if_cancelled`
+    stopped: Arc<AtomicBool>
+}
+
+/// Error returned by `run_cancellable` when the operation was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Cancel the operation with intention to resume it later
+    #[inline]
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+        self.cancel();
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Stop the operation started by `run_cancellable` if it was cancelled
+    ///
+    /// Supposed to be called from the SDK progress callbacks while downloading.
+    /// Never returns if the operation is stopped. Does nothing outside of `run_cancellable`,
+    /// see the module docs
+    pub fn stop_if_cancelled(&self) {
+        if !self.is_cancelled() {
+            return;
+        }
+
+        if !self.stopped.load(Ordering::Relaxed) {
+            if !self.running.load(Ordering::Relaxed) {
+                tracing::warn!("Operation can't be cancelled outside of run_cancellable, it will be finished");
+
+                return;
+            }
+
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+
+        // Worker is abandoned by `run_cancellable`, so the thread is never woken up
+        loop {
+            std::thread::park();
+        }
+    }
+}
+
+/// Run the operation in a worker thread which can be stopped by `CancellationToken::stop_if_cancelled`
+///
+/// Returns `Err(Cancelled)` if the operation was cancelled before it was started or
+/// while it was running. Panics of the operation are resumed in the calling thread
+pub fn run_cancellable<T: Send + 'static>(cancel: &CancellationToken, operation: impl FnOnce() -> T + Send + 'static) -> Result<T, Cancelled> {
+    if cancel.is_cancelled() {
+        return Err(Cancelled);
+    }
+
+    cancel.running.store(true, Ordering::Relaxed);
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    let worker = std::thread::spawn(move || {
+        let _ = sender.send(operation());
+    });
+
+    let result = loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(result) => break Ok(result),
+
+            Err(RecvTimeoutError::Timeout) => {
+                if cancel.stopped.load(Ordering::Relaxed) {
+                    break Err(Cancelled);
+                }
+            }
+
+            // Worker is finished without the result only if it panicked
+            Err(RecvTimeoutError::Disconnected) => {
+                cancel.running.store(false, Ordering::Relaxed);
+
+                match worker.join() {
+                    Ok(()) => unreachable!("Worker finished without the result"),
+                    Err(payload) => std::panic::resume_unwind(payload)
+                }
+            }
+        }
+    };
+
+    cancel.running.store(false, Ordering::Relaxed);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+
+    use super::*;
+
+    /// Downloader calling the progress callback after every chunk, like the SDK one
+    fn download(chunks: u64, written: &AtomicU64, progress: impl Fn(u64, u64)) {
+        for i in 1..=chunks {
+            written.store(i, Ordering::Relaxed);
+
+            progress(i, chunks);
+        }
+    }
+
+    #[test]
+    fn cancel_mid_download() {
+        let cancel = CancellationToken::new();
+        let written = Arc::new(AtomicU64::new(0));
+
+        let result = run_cancellable(&cancel, {
+            let cancel = cancel.clone();
+            let written = written.clone();
+
+            move || {
+                download(100, &written, |curr, _| {
+                    if curr == 30 {
+                        cancel.cancel();
+                    }
+
+                    cancel.stop_if_cancelled();
+                });
+            }
+        });
+
+        assert_eq!(result, Err(Cancelled));
+
+        // Stopped worker doesn't continue the download
+        std::thread::sleep(POLL_INTERVAL);
+
+        assert_eq!(written.load(Ordering::Relaxed), 30);
+    }
+
+    #[test]
+    fn finish_without_cancel() {
+        let cancel = CancellationToken::new();
+        let written = Arc::new(AtomicU64::new(0));
+
+        let result = run_cancellable(&cancel, {
+            let cancel = cancel.clone();
+            let written = written.clone();
+
+            move || {
+                download(100, &written, |_, _| cancel.stop_if_cancelled());
+
+                "done"
+            }
+        });
+
+        assert_eq!(result, Ok("done"));
+        assert_eq!(written.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn cancel_after_download() {
+        let cancel = CancellationToken::new();
+
+        // Unpacking is finished even if the operation was cancelled
+        let result = run_cancellable(&cancel, {
+            let cancel = cancel.clone();
+
+            move || {
+                cancel.cancel();
+
+                std::thread::sleep(POLL_INTERVAL * 2);
+
+                "unpacked"
+            }
+        });
+
+        assert_eq!(result, Ok("unpacked"));
+    }
+
+    #[test]
+    fn pause() {
+        let cancel = CancellationToken::new();
+
+        cancel.pause();
+
+        assert!(cancel.is_cancelled());
+        assert!(cancel.is_paused());
+        assert_eq!(run_cancellable(&cancel, || ()), Err(Cancelled));
+    }
+
+    #[test]
+    fn no_stop_outside_run() {
+        let cancel = CancellationToken::new();
+
+        cancel.cancel();
+        cancel.stop_if_cancelled();
+
+        assert!(!cancel.stopped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    #[should_panic(expected = "other panic")]
+    fn panics_resumed() {
+        let _ = run_cancellable(&CancellationToken::new(), || panic!("other panic"));
+    }
+}
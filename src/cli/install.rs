@@ -0,0 +1,244 @@
+use std::sync::{Arc, Mutex};
+
+use anime_launcher_sdk::components::loader::ComponentsLoader;
+
+use crate::*;
+use crate::cancel::CancellationToken;
+use crate::bandwidth::Throttle;
+use crate::integrity::HashIndex;
+use crate::retry;
+use crate::setup::{self, WineChoice};
+
+use super::progress::TerminalProgress;
+use super::status::{describe_state, describe_diff};
+
+/// Download and install everything needed to launch the game
+///
+/// If `update_only` is set, then the game and voiceovers won't be installed
+/// from scratch, only updated
+pub fn install(update_only: bool) -> anyhow::Result<()> {
+    sync_components()?;
+
+    let mut previous = None;
+
+    loop {
+        let state = LauncherState::get_from_config(|_| {})?;
+
+        tracing::debug!("Launcher state: {state:?}");
+
+        let description = describe_state(&state);
+
+        // Don't repeat the same step forever if it didn't change anything
+        if previous.as_ref() == Some(&description) {
+            anyhow::bail!("Launcher state didn't change after the installation step: {description}");
+        }
+
+        previous = Some(description);
+
+        match state {
+            LauncherState::Launch |
+            LauncherState::PredownloadAvailable { .. } => {
+                println!("Game is installed and ready to launch");
+
+                return Ok(());
+            }
+
+            LauncherState::FolderMigrationRequired { from, to, cleanup_folder } => {
+                println!("Migrating folders: {} -> {}", from.to_string_lossy(), to.to_string_lossy());
+
+                move_files::move_files(&from, &to, &CancellationToken::new())?;
+
+                if let Some(cleanup_folder) = cleanup_folder {
+                    std::fs::remove_dir_all(cleanup_folder)?;
+                }
+            }
+
+            LauncherState::TelemetryNotDisabled => disable_telemetry()?,
+
+            LauncherState::WineNotInstalled => download_wine()?,
+            LauncherState::PrefixNotExists  => create_prefix()?,
+
+            LauncherState::GameNotInstalled(_) |
+            LauncherState::VoiceNotInstalled(_) if update_only => {
+                anyhow::bail!("Can't update the game: {}. Use `install` command instead", describe_state(&state));
+            }
+
+            LauncherState::GameUpdateAvailable(diff) |
+            LauncherState::GameNotInstalled(diff) |
+            LauncherState::VoiceUpdateAvailable(diff) |
+            LauncherState::VoiceNotInstalled(diff) => download_diff(diff)?,
+
+            LauncherState::GameOutdated(_) |
+            LauncherState::VoiceOutdated(_) => anyhow::bail!("Can't update the game: {}", describe_state(&state))
+        }
+    }
+}
+
+/// Download the game update in advance
+pub fn predownload() -> anyhow::Result<()> {
+    let state = LauncherState::get_from_config(|_| {})?;
+
+    let LauncherState::PredownloadAvailable { game } = state else {
+        anyhow::bail!("Pre-download is not available: {}", describe_state(&state));
+    };
+
+    let config = Config::get()?;
+    let temp = config.launcher.temp.unwrap_or_else(std::env::temp_dir);
+
+    let progress = Arc::new(Mutex::new(TerminalProgress::new("Downloading")));
+
+    println!("Pre-downloading {} update", game.latest());
+
+    let throttle = Throttle::new();
+
+    let result = retry::run("Update predownloading", &CancellationToken::new(), || game.download_to(&temp, {
+        let progress = progress.clone();
+        let throttle = throttle.clone();
+
+        move |curr, total| {
+            throttle.update(curr);
+
+            progress.lock().unwrap().update(curr, total);
+        }
+    }));
+
+    progress.lock().unwrap().finish();
+
+    result?;
+
+    println!("Update pre-downloaded");
+
+    Ok(())
+}
+
+/// Make sure the components index is synced with its remote servers
+fn sync_components() -> anyhow::Result<()> {
+    let components = ComponentsLoader::new(&CONFIG.components.path);
+
+    if components.is_sync(&CONFIG.components.servers)?.is_some() {
+        return Ok(());
+    }
+
+    println!("Updating components index");
+
+    for host in &CONFIG.components.servers {
+        match bandwidth::count_folder(&CONFIG.components.path, || components.sync(host)) {
+            Ok(changes) => {
+                for line in changes {
+                    println!("- {line}");
+                }
+
+                return Ok(());
+            }
+
+            Err(err) => tracing::error!("Failed to sync components index with {host}: {err}")
+        }
+    }
+
+    anyhow::bail!("Failed to sync components index")
+}
+
+fn download_diff(mut diff: VersionDiff) -> anyhow::Result<()> {
+    let config = Config::get()?;
+    let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
+    let previous_version = crate::integrity::game_version(&game_path);
+
+    if let Some(temp) = config.launcher.temp {
+        diff = diff.with_temp_folder(temp);
+    }
+
+    println!("Installing {}", describe_diff(&diff));
+
+    let progress = Arc::new(Mutex::new(TerminalProgress::new("Downloading")));
+
+    let throttle = Throttle::new();
+    let errors = retry::ReportedErrors::new();
+
+    let result = retry::run("Game downloading", &CancellationToken::new(), || {
+        let result = diff.install_to(&game_path, {
+            let progress = progress.clone();
+            let throttle = throttle.clone();
+            let errors = errors.clone();
+
+            move |state| {
+                if let DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingProgress(curr, _)) = &state {
+                    throttle.update(*curr);
+                }
+
+                errors.update(&state);
+
+                progress.lock().unwrap().update_from_state(&state);
+            }
+        });
+
+        errors.check(result)
+    });
+
+    progress.lock().unwrap().finish();
+
+    result??;
+
+    // Verified game files were changed by the update
+    if let Err(err) = HashIndex::update_version(&game_path, previous_version.as_deref()) {
+        tracing::error!("Failed to update verification index: {err}");
+    }
+
+    Ok(())
+}
+
+fn download_wine() -> anyhow::Result<()> {
+    let config = Config::get()?;
+
+    let wine = match setup::choose_wine(&config)? {
+        WineChoice::Downloaded(name) => {
+            println!("Selecting downloaded wine version: {name}");
+
+            return setup::select_wine(config, name);
+        }
+
+        WineChoice::Download(wine) => wine
+    };
+
+    println!("Installing wine: {}", wine.title);
+
+    let progress = Arc::new(Mutex::new(TerminalProgress::new("Downloading")));
+
+    let result = setup::install_wine(config, &wine, &CancellationToken::new(), {
+        let progress = progress.clone();
+        let throttle = Throttle::new();
+
+        move |state| {
+            if let InstallerUpdate::DownloadingProgress(curr, _) = &state {
+                throttle.update(*curr);
+            }
+
+            progress.lock().unwrap().update_from_state(&DiffUpdate::InstallerUpdate(state));
+        }
+    });
+
+    progress.lock().unwrap().finish();
+
+    if let Err(err) = result {
+        anyhow::bail!("Failed to install wine: {err}");
+    }
+
+    Ok(())
+}
+
+fn create_prefix() -> anyhow::Result<()> {
+    let config = Config::get()?;
+
+    println!("Creating wine prefix: {}", config.game.wine.prefix.to_string_lossy());
+
+    setup::create_prefix(&config)?;
+
+    Ok(())
+}
+
+fn disable_telemetry() -> anyhow::Result<()> {
+    let config = Config::get()?;
+
+    println!("Disabling telemetry servers");
+
+    setup::disable_telemetry(&config)
+}
@@ -0,0 +1,18 @@
+use crate::*;
+
+use super::status::describe_state;
+
+pub fn launch() -> anyhow::Result<()> {
+    let state = LauncherState::get_from_config(|_| {})?;
+
+    match state {
+        LauncherState::Launch |
+        LauncherState::PredownloadAvailable { .. } => {
+            println!("Launching the game");
+
+            anime_launcher_sdk::zzz::game::run()
+        }
+
+        _ => anyhow::bail!("Can't launch the game: {}", describe_state(&state))
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+mod progress;
+pub mod status;
+mod install;
+mod repair;
+mod launch;
+
+#[derive(Debug, Parser)]
+#[command(name = "sleepy-launcher", version = crate::APP_VERSION, about)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Force debug output
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Run the game if it's ready instead of opening the launcher window
+    #[arg(long)]
+    pub run_game: bool,
+
+    /// Run the game even if its update can be pre-downloaded
+    #[arg(long)]
+    pub just_run_game: bool,
+
+    /// Disable verbose tracing output in stdout
+    #[arg(long)]
+    pub no_verbose_tracing: bool,
+
+    /// Switch active game session prior running the launcher
+    #[arg(long, value_name = "NAME")]
+    pub session: Option<String>,
+
+    /// Use another config file. Other launcher data will be stored next to it
+    #[arg(long, value_name = "PATH", value_parser = parse_config_path, conflicts_with = "data_dir")]
+    pub config: Option<PathBuf>,
+
+    /// Use another folder to store launcher data
+    #[arg(long, value_name = "PATH", value_parser = parse_data_dir)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Store all launcher data next to its executable.
+    /// Enabled by default if `.portable` file exists there
+    #[arg(long, conflicts_with_all = ["config", "data_dir"])]
+    pub portable: bool
+}
+
+impl Args {
+    /// Check if the launcher runs in portable mode
+    pub fn is_portable(&self) -> bool {
+        self.data_dir.is_none() && self.config.is_none() && (self.portable || crate::portable::marker_exists())
+    }
+
+    /// Get launcher folder overridden by `--data-dir`, `--config` or `--portable` flags
+    pub fn launcher_folder(&self) -> Option<PathBuf> {
+        match (&self.data_dir, &self.config) {
+            (Some(folder), _) => Some(folder.to_owned()),
+            (_, Some(config)) => config.parent().map(Path::to_path_buf),
+
+            _ if self.is_portable() => crate::portable::folder(),
+
+            _ => None
+        }
+    }
+}
+
+fn parse_config_path(path: &str) -> Result<PathBuf, String> {
+    let path = std::path::absolute(path).map_err(|err| err.to_string())?;
+
+    // The SDK always reads config.json file from the launcher folder
+    if path.file_name() != Some("config.json".as_ref()) {
+        return Err(String::from("config file must be named config.json"));
+    }
+
+    if path.exists() && !path.is_file() {
+        return Err(String::from("path is not a file"));
+    }
+
+    Ok(path)
+}
+
+fn parse_data_dir(path: &str) -> Result<PathBuf, String> {
+    let path = std::path::absolute(path).map_err(|err| err.to_string())?;
+
+    if path.exists() && !path.is_dir() {
+        return Err(String::from("path is not a folder"));
+    }
+
+    Ok(path)
+}
+
+/// Headless command which can be performed without opening the launcher window
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Print current launcher state
+    Status {
+        /// Print state as a JSON document
+        #[arg(long)]
+        json: bool
+    },
+
+    /// Install everything needed to launch the game
+    Install,
+
+    /// Update installed game and voiceovers
+    Update,
+
+    /// Pre-download the game update if it's available
+    Predownload,
+
+    /// Verify game files and repair broken ones
+    Repair {
+        /// Only print broken files without repairing them
+        #[arg(long)]
+        verify_only: bool,
+
+        /// Hash all the files, even unchanged since their last verification
+        #[arg(long)]
+        full: bool,
+
+        /// Save verification report as a JSON document
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>
+    },
+
+    /// Launch the game if it's ready
+    Launch
+}
+
+impl Command {
+    pub fn run(&self) -> anyhow::Result<()> {
+        tracing::info!("Running headless command: {self:?}");
+
+        match self {
+            Self::Status { json } => status::status(*json),
+            Self::Install         => install::install(false),
+            Self::Update          => install::install(true),
+            Self::Predownload     => install::predownload(),
+            Self::Repair { verify_only, full, report } => repair::repair(*verify_only, *full, report.as_deref()),
+            Self::Launch          => launch::launch()
+        }
+    }
+}
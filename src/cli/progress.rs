@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::*;
+
+/// Minimal interval between two redraws of the progress line
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Terminal alternative to the `ProgressBar` component
+///
+/// Progress is printed to stderr so stdout stays clean for the commands' output
+pub struct TerminalProgress {
+    caption: String,
+
+    /// Add `(XX MB of YY MB)` suffix
+    display_fraction: bool,
+
+    last_redraw: Option<Instant>
+}
+
+impl TerminalProgress {
+    pub fn new(caption: impl ToString) -> Self {
+        Self {
+            caption: caption.to_string(),
+            display_fraction: true,
+            last_redraw: None
+        }
+    }
+
+    /// Finish current progress line and start a new one with given caption
+    pub fn set_caption(&mut self, caption: impl ToString) {
+        self.finish();
+
+        self.caption = caption.to_string();
+        self.display_fraction = true;
+    }
+
+    pub fn display_fraction(&mut self, value: bool) {
+        self.display_fraction = value;
+    }
+
+    /// (current, total)
+    pub fn update(&mut self, curr: u64, total: u64) {
+        let now = Instant::now();
+
+        if let Some(last_redraw) = self.last_redraw {
+            if curr < total && now.duration_since(last_redraw) < REDRAW_INTERVAL {
+                return;
+            }
+        }
+
+        self.last_redraw = Some(now);
+
+        let fraction = if total > 0 {
+            curr as f64 / total as f64
+        } else {
+            0.0
+        };
+
+        let mut line = format!("{}: {:.2}%", self.caption, fraction * 100.0);
+
+        if self.display_fraction {
+            line = format!("{line} ({} of {})", prettify_bytes(curr), prettify_bytes(total));
+        }
+
+        let mut stderr = std::io::stderr().lock();
+
+        #[allow(unused_must_use)] {
+            write!(stderr, "\r\x1b[2K{line}");
+
+            stderr.flush();
+        }
+    }
+
+    /// Move to the next line if the progress was printed
+    pub fn finish(&mut self) {
+        if self.last_redraw.take().is_some() {
+            eprintln!();
+        }
+    }
+
+    pub fn update_from_state(&mut self, state: &DiffUpdate) {
+        match state {
+            DiffUpdate::CheckingFreeSpace(_) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::CheckingFreeSpace(_)) => self.set_caption("Checking free space"),
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingStarted(_))         => self.set_caption("Downloading"),
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissionsStarted(_)) => self.set_caption("Updating permissions"),
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingStarted(_))           => self.set_caption("Unpacking"),
+
+            DiffUpdate::ApplyingHdiffStarted => {
+                self.set_caption("Applying hdiff patches");
+                self.display_fraction(false);
+            }
+
+            DiffUpdate::RemovingOutdatedStarted => {
+                self.set_caption("Removing outdated files");
+                self.display_fraction(false);
+            }
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingProgress(curr, total)) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissions(curr, total)) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingProgress(curr, total)) |
+            DiffUpdate::ApplyingHdiffProgress(curr, total) |
+            DiffUpdate::RemovingOutdatedProgress(curr, total) => self.update(*curr, *total),
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingFinished) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissionsFinished) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingFinished) |
+            DiffUpdate::ApplyingHdiffFinished |
+            DiffUpdate::RemovingOutdatedFinished => self.finish(),
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingError(err)) => {
+                self.finish();
+
+                eprintln!("Downloading error: {err}");
+            }
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingError(err)) => {
+                self.finish();
+
+                eprintln!("Unpacking error: {err}");
+            }
+        }
+    }
+}
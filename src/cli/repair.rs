@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use crate::*;
+use crate::integrity::FileIssue;
+use crate::cancel::CancellationToken;
+
+use super::progress::TerminalProgress;
+
+pub fn repair(verify_only: bool, force_full: bool, report_path: Option<&Path>) -> anyhow::Result<()> {
+    let config = Config::get()?;
+    let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
+
+    println!("Fetching integrity files list");
+
+    let files = repairer::try_get_integrity_files(config.launcher.edition, None)?;
+
+    let mut progress = TerminalProgress::new("Verifying files");
+
+    let options = integrity::VerifyOptions::from_config(&config, force_full);
+
+    let report = integrity::verify(files, &game_path, options, &CancellationToken::new(), |state| {
+        progress.update(state.processed, state.total);
+    });
+
+    progress.finish();
+
+    if report.skipped_files > 0 {
+        println!("Skipped {} files which were not changed since their last verification", report.skipped_files);
+    }
+
+    if let Some(path) = report_path {
+        report.export(path)?;
+
+        println!("Verification report saved to {}", path.to_string_lossy());
+    }
+
+    if report.broken.is_empty() {
+        println!("No broken files found");
+
+        return Ok(());
+    }
+
+    println!("Found {} broken files:", report.broken.len());
+
+    for file in &report.broken {
+        let issue = match file.issue {
+            FileIssue::Missing => String::from("missing"),
+
+            FileIssue::SizeMismatch => format!("size {} instead of {}", file.actual_size.unwrap_or_default(), file.expected_size),
+
+            FileIssue::HashMismatch => format!("hash {} instead of {}", file.actual_hash.as_deref().unwrap_or("unknown"), file.expected_hash)
+        };
+
+        println!("- {} ({issue})", file.path.to_string_lossy());
+    }
+
+    if verify_only {
+        return Ok(());
+    }
+
+    let total = report.broken.len() as u64;
+    let mut failed = 0;
+
+    progress.set_caption("Repairing files");
+    progress.display_fraction(false);
+
+    for (i, file) in report.broken.into_iter().enumerate() {
+        tracing::debug!("Repairing file: {}", file.path.to_string_lossy());
+
+        if let Err(err) = file.repair(&game_path) {
+            tracing::error!("Failed to repair game file {}: {err}", file.path.to_string_lossy());
+
+            failed += 1;
+        }
+
+        progress.update(i as u64 + 1, total);
+    }
+
+    progress.finish();
+
+    if failed > 0 {
+        anyhow::bail!("Failed to repair {failed} game files");
+    }
+
+    println!("Game files repaired");
+
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use serde_json::json;
+
+use crate::*;
+
+/// Get human readable description of the launcher state
+pub fn describe_state(state: &LauncherState) -> String {
+    match state {
+        LauncherState::Launch => String::from("ready to launch"),
+
+        LauncherState::PredownloadAvailable { game } => format!("ready to launch, {} update pre-download available", game.latest()),
+
+        LauncherState::FolderMigrationRequired { from, to, .. } => format!("folder migration required: {} -> {}", from.to_string_lossy(), to.to_string_lossy()),
+
+        LauncherState::TelemetryNotDisabled => String::from("telemetry servers are not disabled"),
+        LauncherState::WineNotInstalled     => String::from("wine is not installed"),
+        LauncherState::PrefixNotExists      => String::from("wine prefix is not created"),
+
+        LauncherState::GameUpdateAvailable(diff)  => format!("game update available: {}", describe_diff(diff)),
+        LauncherState::GameNotInstalled(diff)     => format!("game is not installed: {}", describe_diff(diff)),
+        LauncherState::VoiceUpdateAvailable(diff) => format!("voiceover update available: {}", describe_diff(diff)),
+        LauncherState::VoiceNotInstalled(diff)    => format!("voiceover is not installed: {}", describe_diff(diff)),
+
+        LauncherState::GameOutdated(diff)  => format!("game is too outdated and can't be updated: {}", describe_diff(diff)),
+        LauncherState::VoiceOutdated(diff) => format!("voiceover is too outdated and can't be updated: {}", describe_diff(diff))
+    }
+}
+
+/// Get human readable description of the version difference
+pub fn describe_diff(diff: &VersionDiff) -> String {
+    let size = match diff.downloaded_size() {
+        Some(size) => format!(" ({})", prettify_bytes(size)),
+        None => String::new()
+    };
+
+    match diff {
+        VersionDiff::Latest { version, .. } => format!("{version} (latest)"),
+
+        VersionDiff::Predownload { current, latest, .. } |
+        VersionDiff::Diff { current, latest, .. } => format!("{current} -> {latest}{size}"),
+
+        VersionDiff::Outdated { current, latest, .. } => format!("{current} (latest: {latest})"),
+
+        VersionDiff::NotInstalled { .. } => format!("{}{size}", diff.latest())
+    }
+}
+
+/// Version of the JSON status document format
+///
+/// Must be increased on any breaking change in the document's structure
+pub const STATUS_FORMAT_VERSION: u64 = 1;
+
+/// Get machine readable representation of the launcher state
+pub fn state_json(state: &LauncherState) -> serde_json::Value {
+    match state {
+        LauncherState::Launch => json!({ "name": "launch" }),
+
+        LauncherState::PredownloadAvailable { game } => json!({
+            "name": "predownload_available",
+            "diff": diff_json(game)
+        }),
+
+        LauncherState::FolderMigrationRequired { from, to, cleanup_folder } => json!({
+            "name": "folder_migration_required",
+            "from": from,
+            "to": to,
+            "cleanup_folder": cleanup_folder
+        }),
+
+        LauncherState::TelemetryNotDisabled => json!({ "name": "telemetry_not_disabled" }),
+        LauncherState::WineNotInstalled     => json!({ "name": "wine_not_installed" }),
+        LauncherState::PrefixNotExists      => json!({ "name": "prefix_not_exists" }),
+
+        LauncherState::GameUpdateAvailable(diff)  => json!({ "name": "game_update_available",  "diff": diff_json(diff) }),
+        LauncherState::GameNotInstalled(diff)     => json!({ "name": "game_not_installed",     "diff": diff_json(diff) }),
+        LauncherState::VoiceUpdateAvailable(diff) => json!({ "name": "voice_update_available", "diff": diff_json(diff) }),
+        LauncherState::VoiceNotInstalled(diff)    => json!({ "name": "voice_not_installed",    "diff": diff_json(diff) }),
+        LauncherState::GameOutdated(diff)         => json!({ "name": "game_outdated",          "diff": diff_json(diff) }),
+        LauncherState::VoiceOutdated(diff)        => json!({ "name": "voice_outdated",         "diff": diff_json(diff) })
+    }
+}
+
+/// Get machine readable representation of the version difference
+pub fn diff_json(diff: &VersionDiff) -> serde_json::Value {
+    let (status, current) = match diff {
+        VersionDiff::Latest { version, .. }       => ("latest", Some(version)),
+        VersionDiff::Predownload { current, .. }  => ("predownload", Some(current)),
+        VersionDiff::Diff { current, .. }         => ("diff", Some(current)),
+        VersionDiff::Outdated { current, .. }     => ("outdated", Some(current)),
+        VersionDiff::NotInstalled { .. }          => ("not_installed", None)
+    };
+
+    json!({
+        "status": status,
+        "current": current.map(|version| version.to_string()),
+        "latest": diff.latest().to_string(),
+        "downloaded_size": diff.downloaded_size(),
+        "unpacked_size": diff.unpacked_size()
+    })
+}
+
+/// Collect the same information the main window shows
+/// into one JSON document
+pub fn status_json() -> anyhow::Result<serde_json::Value> {
+    let config = Config::get()?;
+
+    let state = LauncherState::get_from_config(|_| {})?;
+
+    let diff = match GAME.try_get_diff() {
+        Ok(diff) => Some(diff),
+        Err(err) => {
+            tracing::error!("Failed to find game diff: {err}");
+
+            None
+        }
+    };
+
+    // Same check as the one used for the pre-download button
+    let predownload = match &state {
+        LauncherState::PredownloadAvailable { game } => {
+            let temp = config.launcher.temp.clone().unwrap_or_else(std::env::temp_dir);
+
+            let downloaded = game.file_name()
+                .and_then(|file_name| temp.join(file_name).metadata().ok())
+                .map(|metadata| Some(metadata.len()) >= game.downloaded_size())
+                .unwrap_or(false);
+
+            json!({
+                "version": game.latest().to_string(),
+                "downloaded_size": game.downloaded_size(),
+                "downloaded": downloaded
+            })
+        }
+
+        _ => serde_json::Value::Null
+    };
+
+    let dxvk = config.get_selected_dxvk()
+        .ok()
+        .flatten()
+        .map(|version| version.name);
+
+    Ok(json!({
+        "format": STATUS_FORMAT_VERSION,
+
+        "launcher": {
+            "version": APP_VERSION,
+            "first_run": FIRST_RUN_FILE.exists()
+        },
+
+        "state": state_json(&state),
+
+        "game": {
+            "edition": format!("{:?}", config.launcher.edition).to_lowercase(),
+            "path": config.game.path.for_edition(config.launcher.edition),
+            "diff": diff.as_ref().map(diff_json),
+            "predownload": predownload
+        },
+
+        "wine": {
+            "selected": config.game.wine.selected
+        },
+
+        "dxvk": {
+            "selected": dxvk
+        },
+
+        "session": Sessions::get_current().unwrap_or_default()
+    }))
+}
+
+pub fn status(json: bool) -> anyhow::Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status_json()?)?);
+
+        return Ok(());
+    }
+
+    let config = Config::get()?;
+
+    let state = LauncherState::get_from_config(|_| {})?;
+
+    println!("Launcher state: {}", describe_state(&state));
+    println!("Game edition: {:?}", config.launcher.edition);
+    println!("Game path: {}", config.game.path.for_edition(config.launcher.edition).to_string_lossy());
+
+    match GAME.try_get_diff() {
+        Ok(diff) => println!("Game version: {}", describe_diff(&diff)),
+        Err(err) => println!("Game version: unknown ({err})")
+    }
+
+    match &config.game.wine.selected {
+        Some(wine) => println!("Wine version: {wine}"),
+        None => println!("Wine version: not selected")
+    }
+
+    match config.get_selected_dxvk() {
+        Ok(Some(dxvk)) => println!("DXVK version: {}", dxvk.name),
+        _ => println!("DXVK version: not selected")
+    }
+
+    match Sessions::get_current() {
+        Ok(Some(session)) => println!("Session: {session}"),
+        _ => println!("Session: none")
+    }
+
+    Ok(())
+}
@@ -0,0 +1,657 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use md5::{Md5, Digest};
+
+use anime_launcher_sdk::anime_game_core::repairer::IntegrityFile;
+use anime_launcher_sdk::anime_game_core::installer::downloader::Downloader;
+
+use crate::*;
+use crate::cancel::CancellationToken;
+use crate::bandwidth::Throttle;
+
+/// Interval between two progress updates while verifying files
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileIssue {
+    Missing,
+    SizeMismatch,
+    HashMismatch
+}
+
+/// Game file which failed verification
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenFile {
+    /// Path relative to the game folder
+    pub path: PathBuf,
+    pub issue: FileIssue,
+
+    pub expected_size: u64,
+    pub actual_size: Option<u64>,
+
+    pub expected_hash: String,
+    pub actual_hash: Option<String>,
+
+    /// Integrity info used to repair the file
+    #[serde(skip)]
+    pub file: IntegrityFile
+}
+
+impl BrokenFile {
+    /// Download the file again within the download speed limit
+    pub fn repair(&self, game_path: &Path) -> anyhow::Result<()> {
+        let mut downloader = Downloader::new(format!("{}/{}", self.file.base_url, self.file.path.to_string_lossy()))?;
+
+        downloader.continue_downloading = false;
+
+        let throttle = Throttle::new();
+
+        if let Err(err) = downloader.download(game_path.join(&self.file.path), move |curr, _| throttle.update(curr)) {
+            anyhow::bail!(err);
+        }
+
+        Ok(())
+    }
+}
+
+/// Game file state remembered after its hash was verified
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub size: u64,
+
+    /// Modification time in nanoseconds since UNIX epoch
+    pub mtime: u64,
+
+    pub hash: String,
+
+    /// Game version the file was verified for
+    pub game_version: Option<String>
+}
+
+impl IndexEntry {
+    /// Check if the file has the same size and modification time as when it was
+    /// verified with the given hash for the given game version
+    ///
+    /// Entries of the unknown game version are never up to date
+    pub fn is_up_to_date(&self, size: u64, mtime: u64, hash: &str, game_version: Option<&str>) -> bool {
+        game_version.is_some()
+            && self.game_version.as_deref() == game_version
+            && self.size == size
+            && self.mtime == mtime
+            && self.hash == hash
+    }
+}
+
+/// Index of the verified game files. Files which were not changed since
+/// their last verification are not hashed again
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HashIndex {
+    pub game_path: PathBuf,
+
+    /// Entries by the file paths relative to the game folder
+    pub files: HashMap<PathBuf, IndexEntry>
+}
+
+impl HashIndex {
+    /// Load index of the game folder from the `VERIFY_INDEX_FILE`
+    ///
+    /// Returns empty index if it doesn't exist or was made for another folder.
+    /// Entries verified for another game version are dropped
+    pub fn load(game_path: &Path, game_version: Option<&str>) -> Self {
+        let index = std::fs::read(VERIFY_INDEX_FILE.as_path()).ok()
+            .and_then(|index| serde_json::from_slice::<Self>(&index).ok())
+            .filter(|index| index.game_path == game_path);
+
+        let mut index = index.unwrap_or_else(|| Self {
+            game_path: game_path.to_path_buf(),
+            files: HashMap::new()
+        });
+
+        index.retain_version(game_version);
+
+        index
+    }
+
+    /// Remove entries which were not verified for the given game version
+    pub fn retain_version(&mut self, game_version: Option<&str>) {
+        self.files.retain(|_, entry| game_version.is_some() && entry.game_version.as_deref() == game_version);
+    }
+
+    /// Keep entries of the files which were not changed by the game update
+    /// from the `previous_version`, and move them to the installed game version
+    ///
+    /// Must be called after the game files were changed by an update.
+    /// Files with new expected hashes are hashed again anyway
+    pub fn update_version(game_path: &Path, previous_version: Option<&str>) -> anyhow::Result<()> {
+        let mut index = Self::load(game_path, previous_version);
+
+        index.files.retain(|path, entry| {
+            std::fs::metadata(game_path.join(path)).ok()
+                .filter(|metadata| metadata.len() == entry.size)
+                .and_then(|metadata| modification_time(&metadata))
+                .map(|mtime| mtime == entry.mtime)
+                .unwrap_or(false)
+        });
+
+        let game_version = game_version(game_path);
+
+        for entry in index.files.values_mut() {
+            entry.game_version.clone_from(&game_version);
+        }
+
+        index.save()
+    }
+
+    /// Write index to the `VERIFY_INDEX_FILE`
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::write(VERIFY_INDEX_FILE.as_path(), serde_json::to_vec(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Amount of threads used to verify files
+    pub threads: u64,
+
+    /// Compare only file sizes
+    pub fast: bool,
+
+    /// Hash all the files, even if they were not changed since their last verification
+    pub force_full: bool
+}
+
+impl VerifyOptions {
+    pub fn from_config(config: &Schema, force_full: bool) -> Self {
+        Self {
+            threads: config.launcher.repairer.threads,
+            fast: config.launcher.repairer.fast,
+            force_full
+        }
+    }
+}
+
+/// Result of the game files verification
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub game_path: PathBuf,
+
+    /// Only file sizes were compared
+    pub fast: bool,
+
+    pub checked_files: usize,
+    pub checked_size: u64,
+
+    /// Amount of files which were not hashed because they weren't changed since their last verification
+    pub skipped_files: usize,
+
+    /// Verification was cancelled, so only some of the files were checked
+    pub cancelled: bool,
+
+    pub broken: Vec<BrokenFile>
+}
+
+impl VerifyReport {
+    /// Write the report to the file as JSON
+    pub fn export(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+/// Get md5 hash of the file without reading it to memory at once
+///
+/// Amount of read bytes is added to the `read_bytes` counter as the file is read.
+/// Returns `Interrupted` error if the verification was cancelled
+pub fn file_hash(path: &Path, read_bytes: &AtomicU64, cancel: &CancellationToken) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Md5::new();
+    let mut buf = vec![0; 1024 * 1024];
+
+    loop {
+        if cancel.is_cancelled() {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+
+        let read = file.read(&mut buf)?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buf[..read]);
+
+        read_bytes.fetch_add(read as u64, Ordering::Relaxed);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn modification_time(metadata: &std::fs::Metadata) -> Option<u64> {
+    let time = metadata.modified().ok()?
+        .duration_since(UNIX_EPOCH).ok()?;
+
+    Some(time.as_nanos() as u64)
+}
+
+/// Result of the game file check
+#[derive(Debug, Clone)]
+pub struct FileCheck {
+    /// `None` if the file is not broken
+    pub broken: Option<BrokenFile>,
+
+    /// State of the file to store in the hash index, if its hash was verified
+    pub entry: Option<IndexEntry>,
+
+    /// File was not hashed because its index entry is up to date
+    pub skipped: bool,
+
+    /// Amount of bytes read to hash the file
+    pub hashed: u64,
+
+    /// Hashing was interrupted because the verification was cancelled
+    pub interrupted: bool
+}
+
+/// Check the game file
+///
+/// Hash is not checked in `fast` mode, and if the file has the same size and modification
+/// time as in its `known` index entry which was verified with the same hash for the same `game_version`
+///
+/// Bytes read to hash the file are added to the `read_bytes` counter
+pub fn check_file(file: &IntegrityFile, game_path: &Path, fast: bool, known: Option<&IndexEntry>, game_version: Option<&str>, read_bytes: &AtomicU64, cancel: &CancellationToken) -> FileCheck {
+    let path = game_path.join(&file.path);
+
+    let mut broken = BrokenFile {
+        path: file.path.clone(),
+        issue: FileIssue::Missing,
+
+        expected_size: file.size,
+        actual_size: None,
+
+        expected_hash: file.md5.to_lowercase(),
+        actual_hash: None,
+
+        file: file.clone()
+    };
+
+    let mut check = FileCheck {
+        broken: None,
+        entry: None,
+        skipped: false,
+        hashed: 0,
+        interrupted: false
+    };
+
+    let Ok(metadata) = path.metadata() else {
+        check.broken = Some(broken);
+
+        return check;
+    };
+
+    broken.actual_size = Some(metadata.len());
+
+    if metadata.len() != file.size {
+        broken.issue = FileIssue::SizeMismatch;
+        check.broken = Some(broken);
+
+        return check;
+    }
+
+    if fast {
+        return check;
+    }
+
+    let mtime = modification_time(&metadata);
+
+    if let (Some(known), Some(mtime)) = (known, mtime) {
+        if known.is_up_to_date(metadata.len(), mtime, &broken.expected_hash, game_version) {
+            check.entry = Some(known.clone());
+            check.skipped = true;
+
+            return check;
+        }
+    }
+
+    let read_before = read_bytes.load(Ordering::Relaxed);
+
+    let hash = file_hash(&path, read_bytes, cancel);
+
+    // Other threads also update the counter, but this file was
+    // completely read if it was hashed since its size is correct
+    check.hashed = match &hash {
+        Ok(_) => metadata.len(),
+        Err(_) => read_bytes.load(Ordering::Relaxed).saturating_sub(read_before).min(metadata.len())
+    };
+
+    match hash {
+        Ok(hash) if hash == broken.expected_hash => {
+            check.entry = mtime.map(|mtime| IndexEntry {
+                size: metadata.len(),
+                mtime,
+                hash,
+                game_version: game_version.map(String::from)
+            });
+        }
+
+        Ok(hash) => {
+            broken.issue = FileIssue::HashMismatch;
+            broken.actual_hash = Some(hash);
+
+            check.broken = Some(broken);
+        }
+
+        // File is not broken, it just wasn't checked
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted && cancel.is_cancelled() => {
+            check.interrupted = true;
+        }
+
+        Err(err) => {
+            tracing::warn!("Failed to read game file {}: {err}", path.to_string_lossy());
+
+            broken.issue = FileIssue::HashMismatch;
+
+            check.broken = Some(broken);
+        }
+    }
+
+    check
+}
+
+/// Get installed game version
+pub fn game_version(game_path: &Path) -> Option<String> {
+    let edition = Config::get().ok()?.launcher.edition;
+
+    Game::new(game_path, edition).get_version().ok()
+        .map(|version| version.to_string())
+}
+
+/// Current state of the game files verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyProgress {
+    /// Amount of processed bytes
+    pub processed: u64,
+    pub total: u64,
+
+    /// Last file which started to be checked
+    pub current_file: Option<PathBuf>,
+
+    /// Hashing speed in bytes per second
+    pub speed: u64
+}
+
+enum WorkerEvent {
+    Started(PathBuf),
+    Finished(IntegrityFile, FileCheck)
+}
+
+/// Verify game files
+///
+/// Files are taken from the shared queue by `options.threads` threads, biggest files first,
+/// so a thread which got a huge file doesn't block others from doing their work
+///
+/// Files verified by their hashes are stored in the hash index, so they're
+/// not hashed again next time unless they're changed or `force_full` option is set
+///
+/// Threads stop taking new files when the verification is cancelled,
+/// and the report contains files checked up to this moment
+///
+/// `progress` is called periodically while files are verified
+pub fn verify(mut files: Vec<IntegrityFile>, game_path: &Path, options: VerifyOptions, cancel: &CancellationToken, mut progress: impl FnMut(&VerifyProgress)) -> VerifyReport {
+    let game_version = game_version(game_path);
+
+    let stored = if options.fast {
+        HashIndex::default()
+    } else {
+        HashIndex::load(game_path, game_version.as_deref())
+    };
+
+    // Full check hashes all the files again, but the stored
+    // entries are still kept if it's cancelled
+    let index = if options.force_full {
+        Arc::new(HashIndex::default())
+    } else {
+        Arc::new(stored.clone())
+    };
+
+    let total = files.iter().map(|file| file.size).sum::<u64>();
+
+    files.sort_by(|a, b| b.size.cmp(&a.size));
+
+    let queue = Arc::new(Mutex::new(VecDeque::from(files)));
+    let read_bytes = Arc::new(AtomicU64::new(0));
+
+    let (verify_sender, verify_receiver) = std::sync::mpsc::channel();
+
+    for _ in 0..options.threads.max(1) {
+        let thread_sender = verify_sender.clone();
+        let game_path = game_path.to_path_buf();
+        let index = index.clone();
+        let queue = queue.clone();
+        let read_bytes = read_bytes.clone();
+        let cancel = cancel.clone();
+        let game_version = game_version.clone();
+
+        std::thread::spawn(move || {
+            while !cancel.is_cancelled() {
+                // Lock is released right after the file is taken
+                let Some(file) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+
+                if thread_sender.send(WorkerEvent::Started(file.path.clone())).is_err() {
+                    break;
+                }
+
+                let check = check_file(&file, &game_path, options.fast, index.files.get(&file.path), game_version.as_deref(), &read_bytes, &cancel);
+
+                if thread_sender.send(WorkerEvent::Finished(file, check)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // Receiver will return Err when all the threads' senders will be dropped
+    drop(verify_sender);
+
+    let mut broken = Vec::new();
+    let mut checked_files = 0;
+    let mut checked_size = 0;
+    let mut skipped_files = 0;
+
+    let mut new_index = HashIndex {
+        game_path: game_path.to_path_buf(),
+        files: HashMap::new()
+    };
+
+    let mut state = VerifyProgress {
+        processed: 0,
+        total,
+        current_file: None,
+        speed: 0
+    };
+
+    // Sizes of the checked files and amount of bytes read to hash them
+    let mut finished_size = 0;
+    let mut finished_hashed = 0;
+
+    let mut last_update = Instant::now();
+    let mut last_read_bytes = 0;
+
+    progress(&state);
+
+    loop {
+        let finished = match verify_receiver.recv_timeout(PROGRESS_INTERVAL) {
+            Ok(WorkerEvent::Started(path)) => {
+                state.current_file = Some(path);
+
+                false
+            }
+
+            Ok(WorkerEvent::Finished(file, check)) => {
+                finished_size += file.size;
+                finished_hashed += check.hashed;
+
+                if !check.interrupted {
+                    checked_files += 1;
+                    checked_size += file.size;
+                }
+
+                if check.skipped {
+                    skipped_files += 1;
+                }
+
+                if let Some(file) = check.broken {
+                    broken.push(file);
+                }
+
+                if let Some(entry) = check.entry {
+                    new_index.files.insert(file.path, entry);
+                }
+
+                false
+            }
+
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true
+        };
+
+        let elapsed = last_update.elapsed();
+
+        if elapsed >= PROGRESS_INTERVAL || finished {
+            let read = read_bytes.load(Ordering::Relaxed);
+
+            // Files which are being hashed right now are counted by their read bytes
+            state.processed = (finished_size + read.saturating_sub(finished_hashed)).min(total);
+            state.speed = ((read - last_read_bytes) as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+
+            if finished {
+                if !cancel.is_cancelled() {
+                    state.processed = total;
+                }
+
+                state.current_file = None;
+            }
+
+            progress(&state);
+
+            last_update = Instant::now();
+            last_read_bytes = read;
+        }
+
+        if finished {
+            break;
+        }
+    }
+
+    if skipped_files > 0 {
+        tracing::info!("Skipped hashing of {skipped_files} unchanged game files");
+    }
+
+    let cancelled = cancel.is_cancelled();
+
+    if cancelled {
+        tracing::warn!("Game files verification was cancelled after checking {checked_files} files");
+
+        // Keep entries of the files which weren't checked this time
+        let broken_paths = broken.iter()
+            .map(|file| &file.path)
+            .collect::<Vec<_>>();
+
+        for (path, entry) in &stored.files {
+            if !new_index.files.contains_key(path) && !broken_paths.contains(&path) {
+                new_index.files.insert(path.clone(), entry.clone());
+            }
+        }
+    }
+
+    // Sizes-only check doesn't verify hashes so the index stays unchanged
+    if !options.fast {
+        if let Err(err) = new_index.save() {
+            tracing::error!("Failed to save verification index: {err}");
+        }
+    }
+
+    broken.sort_by(|a, b| a.path.cmp(&b.path));
+
+    VerifyReport {
+        game_path: game_path.to_path_buf(),
+        fast: options.fast,
+        checked_files,
+        checked_size,
+        skipped_files,
+        cancelled,
+        broken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(game_version: Option<&str>) -> IndexEntry {
+        IndexEntry {
+            size: 1024,
+            mtime: 1700000000000000000,
+            hash: String::from("d41d8cd98f00b204e9800998ecf8427e"),
+            game_version: game_version.map(String::from)
+        }
+    }
+
+    #[test]
+    fn unchanged_file_skipped() {
+        let entry = entry(Some("1.2.0"));
+
+        assert!(entry.is_up_to_date(1024, 1700000000000000000, "d41d8cd98f00b204e9800998ecf8427e", Some("1.2.0")));
+    }
+
+    #[test]
+    fn changed_file_not_skipped() {
+        let entry = entry(Some("1.2.0"));
+
+        assert!(!entry.is_up_to_date(1025, 1700000000000000000, "d41d8cd98f00b204e9800998ecf8427e", Some("1.2.0")));
+        assert!(!entry.is_up_to_date(1024, 1700000000000000001, "d41d8cd98f00b204e9800998ecf8427e", Some("1.2.0")));
+
+        // Integrity files of the new version expect another hash
+        assert!(!entry.is_up_to_date(1024, 1700000000000000000, "00000000000000000000000000000000", Some("1.2.0")));
+    }
+
+    #[test]
+    fn other_version_not_skipped() {
+        assert!(!entry(Some("1.2.0")).is_up_to_date(1024, 1700000000000000000, "d41d8cd98f00b204e9800998ecf8427e", Some("1.3.0")));
+        assert!(!entry(None).is_up_to_date(1024, 1700000000000000000, "d41d8cd98f00b204e9800998ecf8427e", Some("1.2.0")));
+        assert!(!entry(Some("1.2.0")).is_up_to_date(1024, 1700000000000000000, "d41d8cd98f00b204e9800998ecf8427e", None));
+    }
+
+    #[test]
+    fn retain_version() {
+        let mut index = HashIndex {
+            game_path: PathBuf::from("/game"),
+            files: HashMap::from([
+                (PathBuf::from("old"), entry(Some("1.2.0"))),
+                (PathBuf::from("new"), entry(Some("1.3.0"))),
+                (PathBuf::from("unknown"), entry(None))
+            ])
+        };
+
+        index.retain_version(Some("1.3.0"));
+
+        assert_eq!(index.files.keys().collect::<Vec<_>>(), [&PathBuf::from("new")]);
+
+        index.retain_version(None);
+
+        assert!(index.files.is_empty());
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::*;
+use crate::cancel::CancellationToken;
+
+/// Interval between two journal writes while downloading
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Interval between two progress updates while checking archives
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Held while the journal file is being updated
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Download links and md5 hashes of the game archives by their file names
+pub type Packages = HashMap<String, (String, String)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadKind {
+    /// Game or voice package installation or update
+    Update,
+
+    Predownload
+}
+
+/// Archive which was being downloaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub kind: DownloadKind,
+
+    /// Game version the archive belongs to
+    pub version: String,
+
+    /// Download link from the launcher API. `None` if the archive wasn't found there
+    pub url: Option<String>,
+
+    /// Path to the downloading archive
+    pub target: PathBuf,
+
+    /// Amount of downloaded bytes when the entry was saved. Archive can't be
+    /// shorter than this, otherwise it was changed after the download was interrupted
+    pub offset: u64,
+
+    /// Size of the archive
+    pub total: u64,
+
+    /// md5 hash of the archive from the launcher API
+    pub expected_hash: Option<String>,
+
+    /// UNIX timestamp of the last update of the entry
+    pub updated_at: u64
+}
+
+impl JournalEntry {
+    /// Check if the downloaded part of the archive can be used to continue the download
+    ///
+    /// Completely downloaded archive is compared with its expected hash, and the bytes read
+    /// to hash it are added to the `read_bytes` counter. Archive is not considered broken
+    /// if hashing was cancelled
+    pub fn is_valid(&self, read_bytes: &AtomicU64, cancel: &CancellationToken) -> bool {
+        let Ok(metadata) = self.target.metadata() else {
+            return false;
+        };
+
+        if metadata.len() < self.offset {
+            return false;
+        }
+
+        // Size is unknown if the download was interrupted right after its start
+        if self.total == 0 || metadata.len() < self.total {
+            return true;
+        }
+
+        if metadata.len() > self.total {
+            return false;
+        }
+
+        let Some(expected_hash) = &self.expected_hash else {
+            return true;
+        };
+
+        match integrity::file_hash(&self.target, read_bytes, cancel) {
+            Ok(hash) => hash.eq_ignore_ascii_case(expected_hash),
+
+            // Checked again next time
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted && cancel.is_cancelled() => true,
+
+            Err(_) => false
+        }
+    }
+
+    /// Amount of bytes read by `is_valid` to hash the completely downloaded archive
+    fn hashed_size(&self) -> u64 {
+        let size = self.target.metadata()
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        if self.total > 0 && size == self.total && self.expected_hash.is_some() {
+            size
+        } else {
+            0
+        }
+    }
+}
+
+/// Journal of the downloading archives. Entries are removed when the download is finished
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadJournal {
+    pub entries: Vec<JournalEntry>
+}
+
+impl DownloadJournal {
+    /// Load journal from the `DOWNLOAD_JOURNAL_FILE`. Returns empty journal if it can't be read
+    pub fn load() -> Self {
+        std::fs::read(DOWNLOAD_JOURNAL_FILE.as_path()).ok()
+            .and_then(|journal| serde_json::from_slice(&journal).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write journal to the `DOWNLOAD_JOURNAL_FILE`
+    pub fn save(&self) -> anyhow::Result<()> {
+        if self.entries.is_empty() {
+            if DOWNLOAD_JOURNAL_FILE.exists() {
+                std::fs::remove_file(DOWNLOAD_JOURNAL_FILE.as_path())?;
+            }
+
+            return Ok(());
+        }
+
+        std::fs::write(DOWNLOAD_JOURNAL_FILE.as_path(), serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Load the journal, change it and write it back
+    ///
+    /// Lock is held between loading and writing the journal,
+    /// so concurrent updates from different downloads are not lost
+    pub fn update(update: impl FnOnce(&mut Self)) -> anyhow::Result<()> {
+        let _lock = UPDATE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut journal = Self::load();
+
+        update(&mut journal);
+
+        journal.save()
+    }
+
+    /// Get interrupted download of the given game version
+    pub fn interrupted(&self, kind: DownloadKind, version: &str) -> Option<&JournalEntry> {
+        self.entries.iter()
+            .find(|entry| entry.kind == kind && entry.version == version && entry.target.exists())
+    }
+
+    fn update_entry(entry: JournalEntry) {
+        let result = Self::update(|journal| {
+            journal.entries.retain(|known| known.target != entry.target);
+            journal.entries.push(entry);
+        });
+
+        if let Err(err) = result {
+            tracing::error!("Failed to update download journal: {err}");
+        }
+    }
+
+    /// Remove broken archives of the interrupted downloads of the given kind and their entries
+    ///
+    /// Entries of the missing archives are removed as well. `progress` is called periodically
+    /// with amount of bytes hashed to check completely downloaded archives and their total amount
+    pub fn prepare_resume(&mut self, kind: DownloadKind, cancel: &CancellationToken, progress: impl Fn(u64, u64)) {
+        let total = self.entries.iter()
+            .filter(|entry| entry.kind == kind)
+            .map(JournalEntry::hashed_size)
+            .sum::<u64>();
+
+        let read_bytes = AtomicU64::new(0);
+        let entries = std::mem::take(&mut self.entries);
+
+        self.entries = std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                entries.into_iter()
+                    .filter(|entry| {
+                        if entry.kind != kind || entry.is_valid(&read_bytes, cancel) {
+                            return true;
+                        }
+
+                        if entry.target.exists() {
+                            tracing::warn!("Removing broken archive of the interrupted download: {}", entry.target.to_string_lossy());
+
+                            if let Err(err) = std::fs::remove_file(&entry.target) {
+                                tracing::error!("Failed to remove broken archive: {err}");
+                            }
+                        }
+
+                        false
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            loop {
+                let finished = worker.is_finished();
+
+                if total > 0 {
+                    progress(read_bytes.load(Ordering::Relaxed).min(total), total);
+                }
+
+                if finished {
+                    break;
+                }
+
+                std::thread::sleep(PROGRESS_INTERVAL);
+            }
+
+            worker.join().unwrap_or_else(|err| std::panic::resume_unwind(err))
+        });
+    }
+
+    fn remove_entry(target: &Path) {
+        if let Err(err) = Self::update(|journal| journal.entries.retain(|entry| entry.target != target)) {
+            tracing::error!("Failed to update download journal: {err}");
+        }
+    }
+}
+
+/// Remove broken archives of the interrupted downloads before resuming them
+///
+/// Must be called from the download job since completely downloaded archives are hashed.
+/// See `DownloadJournal::prepare_resume`
+pub fn prepare_resume(kind: DownloadKind, cancel: &CancellationToken, progress: impl Fn(u64, u64)) {
+    let mut prepared = DownloadJournal::load();
+
+    prepared.prepare_resume(kind, cancel, progress);
+
+    // Archives are checked without the lock, so other
+    // downloads could update the journal in the meantime
+    let result = DownloadJournal::update(|journal| {
+        journal.entries.retain(|entry| {
+            entry.kind != kind || prepared.entries.iter().any(|kept| kept.target == entry.target)
+        });
+    });
+
+    if let Err(err) = result {
+        tracing::error!("Failed to update download journal: {err}");
+    }
+}
+
+/// Forget interrupted downloads and remove their archives
+pub fn discard(kind: DownloadKind) {
+    let result = DownloadJournal::update(|journal| {
+        journal.entries.retain(|entry| {
+            if entry.kind != kind {
+                return true;
+            }
+
+            if entry.target.exists() {
+                if let Err(err) = std::fs::remove_file(&entry.target) {
+                    tracing::error!("Failed to remove interrupted download archive: {err}");
+                }
+            }
+
+            false
+        });
+    });
+
+    if let Err(err) = result {
+        tracing::error!("Failed to update download journal: {err}");
+    }
+}
+
+/// Get download links and md5 hashes of all the game archives from the launcher API
+///
+/// Makes a network request, so it's supposed to be called once before the download
+pub fn game_packages() -> Packages {
+    fn collect(value: &serde_json::Value, packages: &mut Packages) {
+        match value {
+            serde_json::Value::Object(object) => {
+                if let (Some(url), Some(md5)) = (object.get("url").and_then(|url| url.as_str()), object.get("md5").and_then(|md5| md5.as_str())) {
+                    if let Some((_, file_name)) = url.rsplit_once('/') {
+                        packages.insert(file_name.to_string(), (url.to_string(), md5.to_string()));
+                    }
+                }
+
+                object.values().for_each(|value| collect(value, packages));
+            }
+
+            serde_json::Value::Array(array) => array.iter().for_each(|value| collect(value, packages)),
+
+            _ => ()
+        }
+    }
+
+    let mut packages = Packages::new();
+
+    match api::get_game_packages() {
+        Ok(value) => collect(&value, &mut packages),
+        Err(err) => tracing::warn!("Failed to get game packages: {err}")
+    }
+
+    packages
+}
+
+/// Keeps journal entries of the downloading archives up to date
+///
+/// Supposed to be used from the SDK progress callbacks
+pub struct JournalRecorder {
+    kind: DownloadKind,
+    version: String,
+    packages: Packages,
+
+    state: Mutex<RecorderState>
+}
+
+struct RecorderState {
+    /// Currently downloading archive
+    entry: Option<JournalEntry>,
+
+    /// All the archives downloaded by the recorder
+    targets: Vec<PathBuf>,
+
+    last_save: Instant
+}
+
+impl JournalRecorder {
+    /// `packages` are used to find download links and hashes of the archives, see `game_packages`
+    pub fn new(kind: DownloadKind, version: impl ToString, packages: Packages) -> Self {
+        Self {
+            kind,
+            version: version.to_string(),
+            packages,
+
+            state: Mutex::new(RecorderState {
+                entry: None,
+                targets: Vec::new(),
+                last_save: Instant::now()
+            })
+        }
+    }
+
+    /// Archive download was started
+    pub fn started(&self, target: &Path) {
+        let (url, expected_hash) = target.file_name()
+            .and_then(|name| self.packages.get(name.to_string_lossy().as_ref()))
+            .cloned()
+            .unzip();
+
+        let entry = JournalEntry {
+            kind: self.kind,
+            version: self.version.clone(),
+            url,
+            target: target.to_path_buf(),
+            offset: target.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            total: 0,
+            expected_hash,
+            updated_at: timestamp()
+        };
+
+        DownloadJournal::update_entry(entry.clone());
+
+        let mut state = self.state.lock().unwrap();
+
+        state.targets.push(entry.target.clone());
+        state.entry = Some(entry);
+        state.last_save = Instant::now();
+    }
+
+    /// Update downloaded bytes amount. Journal is written once per `SAVE_INTERVAL`
+    pub fn progress(&self, offset: u64, total: u64) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        let Some(entry) = &mut state.entry else {
+            return;
+        };
+
+        entry.offset = offset;
+        entry.total = total;
+
+        if state.last_save.elapsed() >= SAVE_INTERVAL {
+            entry.updated_at = timestamp();
+
+            DownloadJournal::update_entry(entry.clone());
+
+            state.last_save = Instant::now();
+        }
+    }
+
+    /// Write the current state of the download, e.g. when it was interrupted
+    ///
+    /// Stopped download may not write its last downloaded bytes,
+    /// so the offset is limited by the archive size
+    pub fn flush(&self) {
+        if let Some(entry) = &mut self.state.lock().unwrap().entry {
+            if let Ok(metadata) = entry.target.metadata() {
+                entry.offset = entry.offset.min(metadata.len());
+            }
+
+            entry.updated_at = timestamp();
+
+            DownloadJournal::update_entry(entry.clone());
+        }
+    }
+
+    /// Download was finished, so the entries are not needed anymore
+    pub fn finished(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        state.entry = None;
+
+        for target in state.targets.drain(..) {
+            DownloadJournal::remove_entry(&target);
+        }
+    }
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// md5 hash of the `ARCHIVE` content
+    const ARCHIVE: &[u8] = b"hello";
+    const ARCHIVE_HASH: &str = "5d41402abc4b2a76b9719d911017c592";
+
+    fn entry(target: PathBuf, offset: u64, total: u64, expected_hash: Option<&str>) -> JournalEntry {
+        JournalEntry {
+            kind: DownloadKind::Update,
+            version: String::from("1.2.0"),
+            url: None,
+            target,
+            offset,
+            total,
+            expected_hash: expected_hash.map(String::from),
+            updated_at: 0
+        }
+    }
+
+    fn is_valid(entry: &JournalEntry) -> bool {
+        entry.is_valid(&AtomicU64::new(0), &CancellationToken::new())
+    }
+
+    #[test]
+    fn missing_archive_invalid() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+
+        assert!(!is_valid(&entry(folder.join("game.zip"), 0, 0, None)));
+    }
+
+    #[test]
+    fn partial_archive_valid() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+        let target = folder.join("game.zip");
+
+        std::fs::write(&target, &ARCHIVE[..3]).unwrap();
+
+        assert!(is_valid(&entry(target.clone(), 0, 0, None)));
+        assert!(is_valid(&entry(target.clone(), 2, 5, Some(ARCHIVE_HASH))));
+
+        // Archive was truncated after the download was interrupted
+        assert!(!is_valid(&entry(target, 4, 5, Some(ARCHIVE_HASH))));
+    }
+
+    #[test]
+    fn complete_archive_hashed() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+        let target = folder.join("game.zip");
+
+        std::fs::write(&target, ARCHIVE).unwrap();
+
+        let read_bytes = AtomicU64::new(0);
+
+        assert!(entry(target.clone(), 5, 5, Some(ARCHIVE_HASH)).is_valid(&read_bytes, &CancellationToken::new()));
+        assert_eq!(read_bytes.load(Ordering::Relaxed), 5);
+
+        assert!(!is_valid(&entry(target.clone(), 5, 5, Some("00000000000000000000000000000000"))));
+        assert!(is_valid(&entry(target.clone(), 5, 5, None)));
+
+        // Archive is bigger than expected
+        assert!(!is_valid(&entry(target, 4, 4, None)));
+    }
+
+    #[test]
+    fn cancelled_hashing_valid() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+        let target = folder.join("game.zip");
+
+        std::fs::write(&target, ARCHIVE).unwrap();
+
+        let cancel = CancellationToken::new();
+
+        cancel.cancel();
+
+        assert!(entry(target, 5, 5, Some("00000000000000000000000000000000")).is_valid(&AtomicU64::new(0), &cancel));
+    }
+
+    #[test]
+    fn prepare_resume() {
+        let temp = tempfile::tempdir().unwrap();
+        let folder = temp.path();
+
+        let valid = folder.join("valid.zip");
+        let broken = folder.join("broken.zip");
+        let other = folder.join("other.zip");
+
+        std::fs::write(&valid, ARCHIVE).unwrap();
+        std::fs::write(&broken, ARCHIVE).unwrap();
+        std::fs::write(&other, ARCHIVE).unwrap();
+
+        let mut other_entry = entry(other.clone(), 5, 5, Some("00000000000000000000000000000000"));
+
+        other_entry.kind = DownloadKind::Predownload;
+
+        let mut journal = DownloadJournal {
+            entries: vec![
+                entry(valid.clone(), 5, 5, Some(ARCHIVE_HASH)),
+                entry(broken.clone(), 5, 5, Some("00000000000000000000000000000000")),
+                entry(folder.join("missing.zip"), 0, 0, None),
+                other_entry
+            ]
+        };
+
+        let last_progress = Mutex::new((0, 0));
+
+        journal.prepare_resume(DownloadKind::Update, &CancellationToken::new(), |curr, total| {
+            *last_progress.lock().unwrap() = (curr, total);
+        });
+
+        let targets = journal.entries.iter()
+            .map(|entry| entry.target.clone())
+            .collect::<Vec<_>>();
+
+        // Entries of another kind are not checked
+        assert_eq!(targets, [valid.clone(), other.clone()]);
+
+        assert!(valid.exists());
+        assert!(!broken.exists());
+        assert!(other.exists());
+
+        let (curr, total) = *last_progress.lock().unwrap();
+
+        assert_eq!(total, 10);
+        assert!(curr <= total);
+    }
+}
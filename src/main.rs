@@ -3,6 +3,8 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 use relm4::prelude::*;
 
+use gtk::prelude::*;
+
 use anime_launcher_sdk::config::ConfigExt;
 use anime_launcher_sdk::zzz::config::{Config, Schema};
 
@@ -17,10 +19,27 @@ use anime_launcher_sdk::zzz::sessions::Sessions;
 
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::filter::*;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+
+use clap::Parser;
 
 pub mod move_files;
 pub mod i18n;
+pub mod api;
+pub mod bandwidth;
 pub mod background;
+pub mod news;
+pub mod cancel;
+pub mod integrity;
+pub mod journal;
+pub mod queue;
+pub mod retry;
+pub mod setup;
+pub mod notifications;
+pub mod portable;
+pub mod settings;
+pub mod cli;
+pub mod rpc;
 pub mod ui;
 
 use ui::main::*;
@@ -35,6 +54,9 @@ pub const APP_DEBUG: bool = cfg!(debug_assertions);
 /// Sets to `true` when the `App` component is ready (fully initialized)
 pub static READY: AtomicBool = AtomicBool::new(false);
 
+/// Sets to `true` when the launcher should be started again after its window is closed
+pub static RESTART: AtomicBool = AtomicBool::new(false);
+
 // TODO: get rid of using this function in all the components' events
 //       e.g. by converting preferences pages into Relm4 Components
 /// Check if the app is ready
@@ -64,7 +86,40 @@ lazy_static::lazy_static! {
     /// Path to the processed `background` file. Standard is `$HOME/.cache/anime-game-launcher/background`
     pub static ref PROCESSED_BACKGROUND_FILE: PathBuf = CACHE_FOLDER.join("background");
 
-    /// Path to `.keep-background` file. Used to mark launcher that it shouldn't update background picture
+    /// Path to the file which describes how the processed `background` file was made.
+    /// Used to regenerate it only when the source picture or processing options change
+    /// 
+    /// Standard is `$HOME/.cache/anime-game-launcher/background.json`
+    pub static ref PROCESSED_BACKGROUND_INFO_FILE: PathBuf = CACHE_FOLDER.join("background.json");
+
+    /// Path to the official background pictures cache. Standard is `$HOME/.cache/anime-game-launcher/backgrounds`
+    pub static ref BACKGROUNDS_CACHE_FOLDER: PathBuf = CACHE_FOLDER.join("backgrounds");
+
+    /// Path to the verified game files index. Used to skip hashing of unchanged files
+    /// 
+    /// Standard is `$HOME/.cache/anime-game-launcher/verify-index.json`
+    pub static ref VERIFY_INDEX_FILE: PathBuf = CACHE_FOLDER.join("verify-index.json");
+
+    /// Path to the journal of the downloading archives. Used to resume interrupted updates
+    /// 
+    /// Standard is `$HOME/.cache/anime-game-launcher/downloads.json`
+    pub static ref DOWNLOAD_JOURNAL_FILE: PathBuf = CACHE_FOLDER.join("downloads.json");
+
+    /// Path to the components indexes of all the components servers. Used to find download mirrors of wine and DXVK builds
+    /// 
+    /// Standard is `$HOME/.cache/anime-game-launcher/components-mirrors`
+    pub static ref COMPONENTS_MIRRORS_FOLDER: PathBuf = CACHE_FOLDER.join("components-mirrors");
+
+    /// Path to the launcher API responses cache. Standard is `$HOME/.cache/anime-game-launcher/api`
+    pub static ref API_CACHE_FOLDER: PathBuf = CACHE_FOLDER.join("api");
+
+    /// Path to `launcher.json` file. Stores launcher settings which are not a part of the config
+    /// 
+    /// Standard is `$HOME/.local/share/anime-game-launcher/launcher.json`
+    pub static ref SETTINGS_FILE: PathBuf = LAUNCHER_FOLDER.join("launcher.json");
+
+    /// Path to `.keep-background` file. Was used to mark launcher that it shouldn't update background picture.
+    /// Now it's migrated to the background source setting
     /// 
     /// Standard is `$HOME/.local/share/anime-game-launcher/.keep-background`
     pub static ref KEEP_BACKGROUND_FILE: PathBuf = LAUNCHER_FOLDER.join(".keep-background");
@@ -74,41 +129,73 @@ lazy_static::lazy_static! {
     /// Standard is `$HOME/.local/share/anime-game-launcher/.first-run`
     pub static ref FIRST_RUN_FILE: PathBuf = LAUNCHER_FOLDER.join(".first-run");
 
-    /// Global app's css
-    static ref GLOBAL_CSS: String = format!("
-        progressbar > text {{
-            margin-bottom: 4px;
-        }}
+    /// Path to the control socket. Used to receive JSON-RPC calls from other programs
+    /// 
+    /// Standard is `$HOME/.local/share/anime-game-launcher/control.sock`
+    pub static ref CONTROL_SOCKET_FILE: PathBuf = LAUNCHER_FOLDER.join("control.sock");
+}
+
+/// Global app's css
+const GLOBAL_CSS: &str = "
+    progressbar > text {
+        margin-bottom: 4px;
+    }
+
+    window.classic-style progressbar {
+        background-color: #00000020;
+        border-radius: 16px;
+        padding: 8px 16px;
+    }
+
+    window.classic-style progressbar:hover {
+        background-color: #00000060;
+        color: #ffffff;
+        transition-duration: 0.5s;
+        transition-timing-function: linear;
+    }
+
+    .round-bin {
+        border-radius: 24px;
+    }
+";
 
+thread_local! {
+    /// Classic style background css. Reloaded when the picture or its settings are changed
+    static BACKGROUND_CSS: gtk::CssProvider = gtk::CssProvider::new();
+}
+
+/// Load the current background picture and its focal point to the classic style css.
+/// Must be called from the main thread
+pub fn reload_background_css() {
+    let css = format!("
         window.classic-style {{
             background: url(\"file://{}\");
             background-repeat: no-repeat;
             background-size: cover;
+            background-position: {};
         }}
+    ", PROCESSED_BACKGROUND_FILE.to_string_lossy(), settings::Settings::get_or_default().background.focal_point.css_position());
 
-        window.classic-style progressbar {{
-            background-color: #00000020;
-            border-radius: 16px;
-            padding: 8px 16px;
-        }}
-
-        window.classic-style progressbar:hover {{
-            background-color: #00000060;
-            color: #ffffff;
-            transition-duration: 0.5s;
-            transition-timing-function: linear;
-        }}
-
-        .round-bin {{
-            border-radius: 24px;
-        }}
-    ", PROCESSED_BACKGROUND_FILE.to_string_lossy());
+    BACKGROUND_CSS.with(|provider| provider.load_from_string(&css));
 }
 
 fn main() -> anyhow::Result<()> {
     // Setup custom panic handler
     human_panic::setup_panic!(human_panic::metadata!());
 
+    // Parse arguments
+    let args = cli::Args::parse();
+
+    // Override launcher folders before they're initialized.
+    // The SDK respects these variables so the config file is loaded from there too
+    let portable = args.is_portable();
+    let custom_folder = args.launcher_folder();
+
+    if let Some(folder) = &custom_folder {
+        std::env::set_var("LAUNCHER_FOLDER", folder);
+        std::env::set_var("CACHE_FOLDER", folder.join("cache"));
+    }
+
     // Create launcher folder if it doesn't exist.
     if !LAUNCHER_FOLDER.exists() {
         std::fs::create_dir_all(LAUNCHER_FOLDER.as_path()).expect("Failed to create launcher folder");
@@ -131,43 +218,31 @@ fn main() -> anyhow::Result<()> {
             .expect("Failed to create cache folder");
     }
 
-    // Force debug output
-    let mut force_debug = false;
-
-    // Run the game
-    let mut run_game = false;
-
-    // Force run the game
-    let mut just_run_game = false;
-
-    // Force disable verbose tracing output in stdout
-    let mut no_verbose_tracing = false;
-
-    let args = std::env::args().collect::<Vec<_>>();
-    let mut gtk_args = Vec::new();
-
-    // Parse arguments
-    for i in 0..args.len() {
-        match args[i].as_str() {
-            "--debug"              => force_debug        = true,
-            "--run-game"           => run_game           = true,
-            "--just-run-game"      => just_run_game      = true,
-            "--no-verbose-tracing" => no_verbose_tracing = true,
-
-            "--session" => {
-                // Switch active session prior running the app
-                if let Some(session) = args.get(i + 1) {
-                    Sessions::set_current(session.to_owned())?;
-                }
-            },
-
-            arg => gtk_args.push(arg.to_string())
-        }
-    }
+    let cli::Args {
+        command,
+        debug: force_debug,
+        run_game,
+        just_run_game,
+        no_verbose_tracing,
+        session,
+        ..
+    } = args;
+
+    // Launcher's arguments are already parsed so GTK gets only the program name
+    let gtk_args = std::env::args().take(1).collect::<Vec<_>>();
+
+    // Headless commands print their own output to stdout,
+    // so tracing output is moved to stderr
+    let stdout_writer = if command.is_some() {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
 
     // Prepare stdout logger
     let stdout = tracing_subscriber::fmt::layer()
         .pretty()
+        .with_writer(stdout_writer)
         .with_filter({
             if APP_DEBUG || force_debug {
                 LevelFilter::TRACE
@@ -197,8 +272,115 @@ fn main() -> anyhow::Result<()> {
 
     tracing::info!("Starting application ({APP_VERSION})");
 
+    // Replace old marker file by the background source setting
+    if KEEP_BACKGROUND_FILE.exists() {
+        let result = settings::Settings::update(|settings| {
+            settings.background.source = settings::BackgroundSource::Keep;
+        });
+
+        match result {
+            Ok(_) => std::fs::remove_file(KEEP_BACKGROUND_FILE.as_path())?,
+            Err(err) => tracing::error!("Failed to migrate .keep-background file: {err}")
+        }
+    }
+
+    if portable {
+        tracing::info!("Running in portable mode");
+
+        if let Err(err) = portable::update_config_paths(LAUNCHER_FOLDER.as_path()) {
+            tracing::error!("Failed to update config paths of the portable launcher: {err}");
+        }
+
+        if let Err(err) = portable::use_portable_temp(LAUNCHER_FOLDER.as_path()) {
+            tracing::error!("Failed to set temp folder of the portable launcher: {err}");
+        }
+    }
+
+    // Run headless command without initializing GTK
+    if let Some(command) = command {
+        // Switch active session prior running the command
+        if let Some(session) = session {
+            Sessions::set_current(session)?;
+        }
+
+        if let Err(err) = command.run() {
+            eprintln!("Error: {err}");
+
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
     adw::init().expect("Libadwaita initialization failed");
 
+    // Register the app to find out if another launcher instance is already running
+    let application = relm4::main_application();
+
+    application.set_application_id(Some(APP_ID));
+
+    // Isolated installs shouldn't forward their actions to the main one
+    if custom_folder.is_some() {
+        application.set_flags(gtk::gio::ApplicationFlags::NON_UNIQUE);
+    }
+
+    if let Err(err) = application.register(None::<&gtk::gio::Cancellable>) {
+        tracing::error!("Failed to register application: {err}");
+    }
+
+    // Forward actions to the running instance instead of opening another window
+    if application.is_remote() {
+        tracing::info!("Launcher is already running, forwarding actions to it");
+
+        if let Some(session) = &session {
+            application.activate_action("set-session", Some(&session.to_variant()));
+        }
+
+        if run_game || just_run_game {
+            application.activate_action("run-game", Some(&just_run_game.to_variant()));
+        }
+
+        application.activate_action("present", None);
+
+        // Make sure all the actions are sent before exiting
+        if let Some(connection) = application.dbus_connection() {
+            connection.flush_sync(None::<&gtk::gio::Cancellable>)?;
+        }
+
+        return Ok(());
+    }
+
+    // Switch active session prior running the app
+    if let Some(session) = session {
+        Sessions::set_current(session)?;
+    }
+
+    // Raise the launcher window when another instance is started
+    let present = gtk::gio::SimpleAction::new("present", None);
+
+    present.connect_activate(|_, _| {
+        if let Some(window) = relm4::main_application().active_window() {
+            window.present();
+        }
+    });
+
+    application.add_action(&present);
+
+    // Switch active session when requested by another instance
+    let set_session = gtk::gio::SimpleAction::new("set-session", Some(gtk::glib::VariantTy::STRING));
+
+    set_session.connect_activate(|_, session| {
+        if let Some(session) = session.and_then(|session| session.get::<String>()) {
+            tracing::info!("Switching session to {session}");
+
+            if let Err(err) = Sessions::set_current(session) {
+                tracing::error!("Failed to set current session: {err}");
+            }
+        }
+    });
+
+    application.add_action(&set_session);
+
     // Register and include resources
     gtk::gio::resources_register_include!("resources.gresource")
         .expect("Failed to register resources");
@@ -208,7 +390,17 @@ fn main() -> anyhow::Result<()> {
         .add_resource_path(&format!("{APP_RESOURCE_PATH}/icons"));
 
     // Set global css
-    relm4::set_global_css(&GLOBAL_CSS);
+    relm4::set_global_css(GLOBAL_CSS);
+
+    BACKGROUND_CSS.with(|provider| {
+        gtk::style_context_add_provider_for_display(
+            &gtk::gdk::Display::default().unwrap(),
+            provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION
+        );
+    });
+
+    reload_background_css();
 
     // Set application's title
     gtk::glib::set_application_name("Sleepy Launcher");
@@ -224,11 +416,22 @@ fn main() -> anyhow::Result<()> {
     // Run FirstRun window if .first-run file persist
     if FIRST_RUN_FILE.exists() {
         // Create the app
-        let app = RelmApp::new(APP_ID)
+        let app = RelmApp::from_app(application)
             .with_args(gtk_args);
 
         // Show first run window
         app.run::<FirstRunApp>(());
+
+        // Start the launcher again once this instance has released its application id
+        if RESTART.load(Ordering::Relaxed) {
+            use std::os::unix::process::CommandExt;
+
+            let err = std::process::Command::new(std::env::current_exe()?)
+                .args(std::env::args().skip(1))
+                .exec();
+
+            tracing::error!("Failed to restart the launcher: {err}");
+        }
     }
 
     // Run the app if everything's ready
@@ -255,11 +458,13 @@ fn main() -> anyhow::Result<()> {
         }
 
         // Create the app
-        let app = RelmApp::new(APP_ID)
+        let app = RelmApp::from_app(application)
             .with_args(gtk_args);
 
         // Show main window
         app.run::<App>(());
+
+        rpc::stop();
     }
 
     Ok(())
@@ -1,16 +1,25 @@
 use std::path::Path;
 use std::io::Result;
 
+use crate::cancel::CancellationToken;
+
 /// Move files from one folder to another
-pub fn move_files(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+///
+/// Cancellation is checked between the entries, so files are either moved
+/// or left in the source folder. Returns `Interrupted` error if cancelled
+pub fn move_files(from: impl AsRef<Path>, to: impl AsRef<Path>, cancel: &CancellationToken) -> Result<()> {
     for entry in from.as_ref().read_dir()?.flatten() {
+        if cancel.is_cancelled() {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+
         let source = entry.path();
         let target = to.as_ref().join(entry.file_name());
 
         if std::fs::rename(&source, &target).is_err() {
             if source.is_dir() {
                 std::fs::create_dir_all(&target)
-                    .and_then(|_| move_files(&source, &target))
+                    .and_then(|_| move_files(&source, &target, cancel))
                     .and_then(|_| std::fs::remove_dir_all(&source))?;
             }
 
@@ -0,0 +1,84 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostKind {
+    Announcement,
+    Event,
+
+    /// Patch notes and other news
+    Info
+}
+
+impl PostKind {
+    pub fn list() -> &'static [Self] {
+        &[
+            Self::Announcement,
+            Self::Event,
+            Self::Info
+        ]
+    }
+
+    fn from_api(kind: &str) -> Option<Self> {
+        match kind {
+            "POST_TYPE_ANNOUNCE" => Some(Self::Announcement),
+            "POST_TYPE_ACTIVITY" => Some(Self::Event),
+            "POST_TYPE_INFO"     => Some(Self::Info),
+
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Post {
+    pub kind: PostKind,
+    pub title: String,
+    pub link: String,
+    pub date: String
+}
+
+/// Get posts from the launcher API
+///
+/// Uses cached API response if the API is unreachable
+pub fn get_posts() -> anyhow::Result<Vec<Post>> {
+    let content = crate::api::get_game_content()?;
+
+    let posts = content["posts"].as_array()
+        .ok_or_else(|| anyhow::anyhow!("Failed to list posts in the launcher API"))?
+        .iter()
+        .filter_map(|post| {
+            Some(Post {
+                kind: PostKind::from_api(post["type"].as_str()?)?,
+                title: post["title"].as_str()?.to_owned(),
+                link: post["link"].as_str().filter(|link| is_web_link(link))?.to_owned(),
+                date: post["date"].as_str().unwrap_or_default().to_owned()
+            })
+        })
+        .collect();
+
+    Ok(posts)
+}
+
+/// Check that the link can be safely opened in the browser
+///
+/// Links from the API are opened by the system, so other schemes
+/// (e.g. `file://`) are not allowed
+pub fn is_web_link(link: &str) -> bool {
+    url::Url::parse(link)
+        .map(|url| matches!(url.scheme(), "http" | "https"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_links() {
+        assert!(is_web_link("https://example.com/news/1"));
+        assert!(is_web_link("HTTP://example.com"));
+
+        assert!(!is_web_link("file:///etc/passwd"));
+        assert!(!is_web_link("javascript:alert(1)"));
+        assert!(!is_web_link("/news/1"));
+        assert!(!is_web_link(""));
+    }
+}
@@ -0,0 +1,40 @@
+use gtk::prelude::*;
+use gtk::gio;
+
+use crate::*;
+use crate::settings::{Settings, NotificationEvent};
+
+/// Send desktop notification about finished or failed operation. Must be called from the main thread
+///
+/// Notification of the same event replaces the previous one. Finished downloads and repairs
+/// get the "Launch" button, failed operations get the "Show log" one.
+/// Both are handled by the `app.run-game` and `app.show-log` actions
+pub fn send(event: NotificationEvent, title: &str, body: Option<&str>, success: bool) {
+    if !Settings::get_or_default().notifications.enabled(event) {
+        return;
+    }
+
+    let notification = gio::Notification::new(title);
+
+    notification.set_body(body);
+    notification.set_default_action("app.show-window");
+
+    if !success {
+        notification.set_priority(gio::NotificationPriority::High);
+        notification.add_button(&tr!("show-log"), "app.show-log");
+    }
+
+    else if matches!(event, NotificationEvent::Download | NotificationEvent::Repair) {
+        notification.add_button_with_target_value(&tr!("launch"), "app.run-game", Some(&false.to_variant()));
+    }
+
+    let id = match event {
+        NotificationEvent::Download    => "download",
+        NotificationEvent::Predownload => "predownload",
+        NotificationEvent::Repair      => "repair",
+        NotificationEvent::Wine        => "wine",
+        NotificationEvent::Prefix      => "prefix"
+    };
+
+    relm4::main_application().send_notification(Some(id), &notification);
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use crate::*;
+
+/// Name of the file which enables portable mode when placed next to the launcher's executable
+pub const MARKER_FILE: &str = ".portable";
+
+/// Name of the file in the portable launcher folder which stores its last known location
+const LOCATION_FILE: &str = ".portable-location";
+
+/// Get folder with the launcher's executable
+pub fn executable_folder() -> Option<PathBuf> {
+    std::env::current_exe().ok()?
+        .parent()
+        .map(Path::to_path_buf)
+}
+
+/// Get launcher folder used in portable mode. It's stored next to the launcher's executable
+pub fn folder() -> Option<PathBuf> {
+    executable_folder().map(|folder| folder.join("data"))
+}
+
+/// Check if the portable mode marker file exists next to the launcher's executable
+pub fn marker_exists() -> bool {
+    executable_folder()
+        .map(|folder| folder.join(MARKER_FILE).exists())
+        .unwrap_or(false)
+}
+
+/// Update absolute paths in the config if the portable launcher folder was moved,
+/// e.g. when the external drive was mounted to another location
+pub fn update_config_paths(folder: &Path) -> anyhow::Result<()> {
+    let location_file = folder.join(LOCATION_FILE);
+
+    let previous = std::fs::read_to_string(&location_file)
+        .map(|path| PathBuf::from(path.trim()))
+        .ok();
+
+    if let Some(previous) = previous.filter(|previous| previous != folder) {
+        tracing::info!("Portable folder was moved from {}. Updating config paths", previous.to_string_lossy());
+
+        let mut config = Config::get()?;
+
+        relocate_paths(&mut config, &previous, folder);
+
+        Config::update_raw(config)?;
+    }
+
+    std::fs::write(location_file, folder.to_string_lossy().as_bytes())?;
+
+    Ok(())
+}
+
+/// Keep downloading archives in the portable launcher folder
+/// unless another temp folder than the system one was chosen
+pub fn use_portable_temp(folder: &Path) -> anyhow::Result<()> {
+    let mut config = Config::get()?;
+
+    if let Some(temp) = portable_temp(&config, folder) {
+        tracing::info!("Using portable temp folder: {}", temp.to_string_lossy());
+
+        std::fs::create_dir_all(&temp)?;
+
+        config.launcher.temp = Some(temp);
+
+        Config::update_raw(config)?;
+    }
+
+    Ok(())
+}
+
+/// Get temp folder inside of the portable launcher folder if the config uses the default one
+fn portable_temp(config: &Schema, folder: &Path) -> Option<PathBuf> {
+    match &config.launcher.temp {
+        Some(temp) if temp != &std::env::temp_dir() => None,
+        _ => Some(folder.join("temp"))
+    }
+}
+
+/// Replace `previous` folder with the `folder` in the config paths stored inside of it
+fn relocate_paths(config: &mut Schema, previous: &Path, folder: &Path) {
+    let relocate = |path: &mut PathBuf| {
+        if let Ok(relative) = path.strip_prefix(previous) {
+            *path = folder.join(relative);
+        }
+    };
+
+    relocate(&mut config.game.path.global);
+    relocate(&mut config.game.path.china);
+    relocate(&mut config.game.wine.builds);
+    relocate(&mut config.game.wine.prefix);
+    relocate(&mut config.game.dxvk.builds);
+    relocate(&mut config.components.path);
+
+    if let Some(temp) = &mut config.launcher.temp {
+        relocate(temp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relocate_moved_paths() {
+        let mut config = Schema::default();
+
+        config.game.path.global = PathBuf::from("/mnt/old/data/game");
+        config.game.wine.prefix = PathBuf::from("/mnt/old/data/prefix");
+        config.components.path = PathBuf::from("/mnt/old/data");
+        config.launcher.temp = Some(PathBuf::from("/mnt/old/data/temp"));
+
+        // Paths outside of the portable folder are kept
+        config.game.path.china = PathBuf::from("/home/user/game");
+        config.game.wine.builds = PathBuf::from("/mnt/old/data-backup/wine");
+
+        relocate_paths(&mut config, Path::new("/mnt/old/data"), Path::new("/media/new/data"));
+
+        assert_eq!(config.game.path.global, Path::new("/media/new/data/game"));
+        assert_eq!(config.game.wine.prefix, Path::new("/media/new/data/prefix"));
+        assert_eq!(config.components.path, Path::new("/media/new/data"));
+        assert_eq!(config.launcher.temp.as_deref(), Some(Path::new("/media/new/data/temp")));
+
+        assert_eq!(config.game.path.china, Path::new("/home/user/game"));
+        assert_eq!(config.game.wine.builds, Path::new("/mnt/old/data-backup/wine"));
+    }
+
+    #[test]
+    fn default_temp_replaced() {
+        let folder = Path::new("/media/drive/data");
+        let mut config = Schema::default();
+
+        config.launcher.temp = None;
+
+        assert_eq!(portable_temp(&config, folder), Some(folder.join("temp")));
+
+        config.launcher.temp = Some(std::env::temp_dir());
+
+        assert_eq!(portable_temp(&config, folder), Some(folder.join("temp")));
+
+        // Chosen temp folder is kept
+        config.launcher.temp = Some(PathBuf::from("/media/drive/downloads"));
+
+        assert_eq!(portable_temp(&config, folder), None);
+    }
+}
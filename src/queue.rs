@@ -0,0 +1,474 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::*;
+use crate::cancel::CancellationToken;
+use crate::settings::Settings;
+
+/// Minimal interval between two progress notifications of the queue subscribers
+const NOTIFY_INTERVAL: Duration = Duration::from_millis(250);
+
+pub type JobId = u64;
+
+type JobRunner = Box<dyn FnOnce(Job) + Send>;
+type Listener = Box<dyn Fn(&[JobInfo]) + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Running
+}
+
+/// Public state of the queued job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub title: String,
+    pub state: JobState,
+
+    /// Current step of the running job, e.g. "Downloading"
+    pub caption: Option<String>,
+
+    /// (current, total)
+    pub progress: (u64, u64),
+
+    /// Job can be cancelled from the queue view
+    pub cancellable: bool,
+
+    /// Job can be paused from the queue view
+    pub pausable: bool
+}
+
+struct QueuedJob {
+    info: JobInfo,
+    cancel: CancellationToken,
+    run: JobRunner
+}
+
+struct Queue {
+    pending: VecDeque<QueuedJob>,
+    running: Vec<(JobInfo, CancellationToken)>,
+
+    last_notify: Instant
+}
+
+impl Queue {
+    /// Move pending jobs which should be started now to the running ones
+    ///
+    /// Cancelled jobs are started first and don't take the free slots
+    fn start_jobs(&mut self, max_jobs: usize) -> Vec<QueuedJob> {
+        let mut started = Vec::new();
+
+        while let Some(position) = self.pending.iter().position(|job| job.cancel.is_cancelled()) {
+            if let Some(job) = self.pending.remove(position) {
+                started.push(job);
+            }
+        }
+
+        let cancelled = started.len();
+
+        while self.running.len() + started.len() - cancelled < max_jobs {
+            match self.pending.pop_front() {
+                Some(job) => started.push(job),
+                None => break
+            }
+        }
+
+        for job in &mut started {
+            job.info.state = JobState::Running;
+
+            self.running.push((job.info.clone(), job.cancel.clone()));
+        }
+
+        started
+    }
+
+    /// Returns `true` if the job was moved
+    fn move_job(&mut self, id: JobId, up: bool) -> bool {
+        let Some(position) = self.pending.iter().position(|job| job.info.id == id) else {
+            return false;
+        };
+
+        let target = if up {
+            position.checked_sub(1)
+        } else {
+            Some(position + 1).filter(|target| *target < self.pending.len())
+        };
+
+        match target {
+            Some(target) => {
+                self.pending.swap(position, target);
+
+                true
+            }
+
+            None => false
+        }
+    }
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref QUEUE: Mutex<Queue> = Mutex::new(Queue {
+        pending: VecDeque::new(),
+        running: Vec::new(),
+        last_notify: Instant::now()
+    });
+
+    static ref LISTENERS: Mutex<Vec<Listener>> = Mutex::new(Vec::new());
+}
+
+/// Handle of the running job given to its runner
+#[derive(Debug, Clone)]
+pub struct Job {
+    id: JobId
+}
+
+impl Job {
+    /// Update caption shown in the queue view and reset the job's progress
+    pub fn set_caption(&self, caption: impl ToString) {
+        let caption = caption.to_string();
+
+        crate::rpc::notify("progress", serde_json::json!({
+            "event": "caption",
+            "caption": caption
+        }));
+
+        self.store_caption(caption);
+    }
+
+    /// Update progress shown in the queue view
+    pub fn set_progress(&self, current: u64, total: u64) {
+        let caption = self.store_progress(current, total);
+
+        crate::rpc::notify_progress_values("progress", caption.as_deref(), current, total);
+    }
+
+    /// Update caption and progress from the installation state reported by the SDK
+    pub fn update_from_state(&self, state: &DiffUpdate) {
+        crate::rpc::notify_progress(state);
+
+        match state {
+            DiffUpdate::CheckingFreeSpace(_) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::CheckingFreeSpace(_)) => self.store_caption(tr!("checking-free-space")),
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingStarted(_))         => self.store_caption(tr!("downloading")),
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissionsStarted(_)) => self.store_caption(tr!("updating-permissions")),
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingStarted(_))           => self.store_caption(tr!("unpacking")),
+
+            DiffUpdate::ApplyingHdiffStarted    => self.store_caption(tr!("applying-hdiff")),
+            DiffUpdate::RemovingOutdatedStarted => self.store_caption(tr!("removing-outdated")),
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingProgress(curr, total)) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissions(curr, total)) |
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingProgress(curr, total)) |
+            DiffUpdate::ApplyingHdiffProgress(curr, total) |
+            DiffUpdate::RemovingOutdatedProgress(curr, total) => {
+                self.store_progress(*curr, *total);
+            }
+
+            _ => ()
+        }
+    }
+
+    fn store_caption(&self, caption: String) {
+        let mut queue = QUEUE.lock().unwrap();
+
+        if let Some((info, _)) = queue.running.iter_mut().find(|(info, _)| info.id == self.id) {
+            info.caption = Some(caption);
+            info.progress = (0, 0);
+        }
+
+        notify(queue);
+    }
+
+    /// Returns current caption of the job
+    fn store_progress(&self, current: u64, total: u64) -> Option<String> {
+        let mut queue = QUEUE.lock().unwrap();
+
+        let caption = queue.running.iter_mut()
+            .find(|(info, _)| info.id == self.id)
+            .and_then(|(info, _)| {
+                info.progress = (current, total);
+
+                info.caption.clone()
+            });
+
+        if queue.last_notify.elapsed() >= NOTIFY_INTERVAL {
+            queue.last_notify = Instant::now();
+
+            notify(queue);
+        }
+
+        caption
+    }
+}
+
+/// Add job to the end of the queue
+///
+/// Job is started when all the jobs before it are started and there's a free slot
+/// for it (see `max_jobs` download setting). If `cancel` is given then the job
+/// can be cancelled from the queue view. Cancelled job is started as soon as possible
+/// so it can stop itself properly
+pub fn submit(title: impl ToString, cancel: Option<CancellationToken>, run: impl FnOnce(Job) + Send + 'static) -> JobId {
+    let cancellable = cancel.is_some();
+
+    push(title.to_string(), cancel.unwrap_or_default(), cancellable, false, Box::new(run))
+}
+
+/// Add job which can be paused and cancelled from the queue view to the end of the queue
+///
+/// Paused job is stopped the same way as the cancelled one,
+/// so it must check `CancellationToken::is_paused` to resume later
+pub fn submit_pausable(title: impl ToString, cancel: CancellationToken, run: impl FnOnce(Job) + Send + 'static) -> JobId {
+    push(title.to_string(), cancel, true, true, Box::new(run))
+}
+
+fn push(title: String, cancel: CancellationToken, cancellable: bool, pausable: bool, run: JobRunner) -> JobId {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    let job = QueuedJob {
+        info: JobInfo {
+            id,
+            title,
+            state: JobState::Queued,
+            caption: None,
+            progress: (0, 0),
+            cancellable,
+            pausable
+        },
+
+        cancel,
+        run
+    };
+
+    tracing::info!("Queued job {id}: {}", job.info.title);
+
+    QUEUE.lock().unwrap().pending.push_back(job);
+
+    schedule();
+
+    id
+}
+
+/// Cancel queued or running job
+pub fn cancel(id: JobId) {
+    let queue = QUEUE.lock().unwrap();
+
+    if let Some(token) = find_token(&queue, id, |info| info.cancellable) {
+        tracing::info!("Cancelling job {id}");
+
+        token.cancel();
+    }
+
+    drop(queue);
+
+    schedule();
+}
+
+/// Pause queued or running job to resume it later
+pub fn pause(id: JobId) {
+    let queue = QUEUE.lock().unwrap();
+
+    if let Some(token) = find_token(&queue, id, |info| info.pausable) {
+        tracing::info!("Pausing job {id}");
+
+        token.pause();
+    }
+
+    drop(queue);
+
+    schedule();
+}
+
+fn find_token(queue: &Queue, id: JobId, allowed: impl Fn(&JobInfo) -> bool) -> Option<&CancellationToken> {
+    queue.pending.iter()
+        .find(|job| job.info.id == id)
+        .map(|job| (&job.info, &job.cancel))
+        .or_else(|| {
+            queue.running.iter()
+                .find(|(info, _)| info.id == id)
+                .map(|(info, token)| (info, token))
+        })
+        .filter(|(info, _)| allowed(info))
+        .map(|(_, token)| token)
+}
+
+/// Move queued job one position closer to the queue start or end
+pub fn move_job(id: JobId, up: bool) {
+    let mut queue = QUEUE.lock().unwrap();
+
+    if queue.move_job(id, up) {
+        notify(queue);
+    }
+}
+
+/// Get running and queued jobs in order of their execution
+pub fn jobs() -> Vec<JobInfo> {
+    snapshot(&QUEUE.lock().unwrap())
+}
+
+/// Call `listener` every time the queue is changed
+pub fn subscribe(listener: impl Fn(&[JobInfo]) + Send + 'static) {
+    LISTENERS.lock().unwrap().push(Box::new(listener));
+}
+
+/// Start queued jobs if there are free slots for them
+///
+/// Supposed to be called after cancelling a job token outside of the queue
+pub fn schedule() {
+    let max_jobs = Settings::get_or_default().downloads.max_jobs.max(1) as usize;
+
+    let mut queue = QUEUE.lock().unwrap();
+
+    for job in queue.start_jobs(max_jobs) {
+        let handle = Job {
+            id: job.info.id
+        };
+
+        let run = job.run;
+
+        tracing::info!("Starting job {}: {}", job.info.id, job.info.title);
+
+        std::thread::spawn(move || {
+            let id = handle.id;
+
+            // Job is marked as finished even if it panicked
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || run(handle)));
+
+            finish(id);
+
+            if let Err(payload) = result {
+                std::panic::resume_unwind(payload);
+            }
+        });
+    }
+
+    notify(queue);
+}
+
+fn finish(id: JobId) {
+    tracing::info!("Finished job {id}");
+
+    QUEUE.lock().unwrap().running.retain(|(info, _)| info.id != id);
+
+    schedule();
+}
+
+fn snapshot(queue: &Queue) -> Vec<JobInfo> {
+    queue.running.iter()
+        .map(|(info, _)| info.clone())
+        .chain(queue.pending.iter().map(|job| job.info.clone()))
+        .collect()
+}
+
+/// Send the queue state to the listeners. Consumes the lock to call them without it
+fn notify(queue: std::sync::MutexGuard<'_, Queue>) {
+    let jobs = snapshot(&queue);
+
+    drop(queue);
+
+    for listener in LISTENERS.lock().unwrap().iter() {
+        listener(&jobs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(jobs: u64) -> Queue {
+        Queue {
+            pending: (0..jobs).map(|id| QueuedJob {
+                info: JobInfo {
+                    id,
+                    title: format!("Job {id}"),
+                    state: JobState::Queued,
+                    caption: None,
+                    progress: (0, 0),
+                    cancellable: true,
+                    pausable: false
+                },
+
+                cancel: CancellationToken::new(),
+                run: Box::new(|_| ())
+            }).collect(),
+
+            running: Vec::new(),
+            last_notify: Instant::now()
+        }
+    }
+
+    fn pending(queue: &Queue) -> Vec<JobId> {
+        queue.pending.iter().map(|job| job.info.id).collect()
+    }
+
+    fn ids(jobs: &[QueuedJob]) -> Vec<JobId> {
+        jobs.iter().map(|job| job.info.id).collect()
+    }
+
+    #[test]
+    fn start_jobs() {
+        let mut queue = queue(3);
+
+        let started = queue.start_jobs(2);
+
+        assert_eq!(ids(&started), [0, 1]);
+        assert!(started.iter().all(|job| job.info.state == JobState::Running));
+
+        assert_eq!(queue.running.len(), 2);
+        assert_eq!(pending(&queue), [2]);
+
+        // No free slots
+        assert!(queue.start_jobs(2).is_empty());
+
+        queue.running.remove(0);
+
+        assert_eq!(ids(&queue.start_jobs(2)), [2]);
+        assert!(queue.start_jobs(2).is_empty());
+    }
+
+    #[test]
+    fn start_cancelled_jobs() {
+        let mut queue = queue(3);
+
+        queue.pending[2].cancel.cancel();
+
+        // Cancelled job doesn't take the slot
+        assert_eq!(ids(&queue.start_jobs(1)), [2, 0]);
+        assert_eq!(pending(&queue), [1]);
+
+        queue.pending[0].cancel.cancel();
+
+        // Cancelled job is started even without free slots
+        assert_eq!(ids(&queue.start_jobs(1)), [1]);
+        assert!(queue.pending.is_empty());
+    }
+
+    #[test]
+    fn move_job() {
+        let mut queue = queue(3);
+
+        assert!(queue.move_job(1, true));
+        assert_eq!(pending(&queue), [1, 0, 2]);
+
+        assert!(queue.move_job(1, false));
+        assert!(queue.move_job(1, false));
+        assert_eq!(pending(&queue), [0, 2, 1]);
+
+        // Already at the queue start or end
+        assert!(!queue.move_job(0, true));
+        assert!(!queue.move_job(1, false));
+
+        // Unknown or already started job
+        assert!(!queue.move_job(3, true));
+
+        queue.start_jobs(1);
+
+        assert!(!queue.move_job(0, false));
+        assert_eq!(pending(&queue), [2, 1]);
+    }
+}
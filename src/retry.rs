@@ -0,0 +1,329 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anime_launcher_sdk::components::{wine, dxvk};
+use anime_launcher_sdk::components::loader::ComponentsLoader;
+
+use crate::*;
+use crate::cancel::CancellationToken;
+use crate::settings::Settings;
+
+/// Longest delay between two download attempts
+const MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Delay before the next attempt after `attempt` attempts were failed
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_DELAY)
+}
+
+/// Run the download and repeat it if it failed
+///
+/// Amount of attempts and delays between them are taken from the download settings.
+/// Partially downloaded archives are kept in the temp folder, so every attempt
+/// continues the previous one. Retrying is stopped by the `cancel` token,
+/// and the last error is returned
+pub fn run<T, E: std::fmt::Display>(name: &str, cancel: &CancellationToken, download: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let settings = Settings::get_or_default().downloads;
+
+    run_with(name, settings.retries + 1, Duration::from_secs(settings.retry_delay), cancel, download)
+}
+
+fn run_with<T, E: std::fmt::Display>(name: &str, attempts: u32, base: Duration, cancel: &CancellationToken, mut download: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let mut attempt = 1;
+
+    loop {
+        match download() {
+            Ok(result) => return Ok(result),
+
+            Err(err) if attempt >= attempts => {
+                tracing::error!("{name}: attempt {attempt} of {attempts} failed, giving up: {err}");
+
+                return Err(err);
+            }
+
+            Err(err) => {
+                let delay = backoff(base, attempt);
+
+                tracing::warn!("{name}: attempt {attempt} of {attempts} failed, retrying in {delay:?}: {err}");
+
+                sleep(delay, cancel);
+
+                if cancel.is_cancelled() {
+                    tracing::warn!("{name}: retrying was cancelled");
+
+                    return Err(err);
+                }
+
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Errors reported through the installation updates
+///
+/// SDK can finish the game installation successfully even if they happened,
+/// so they must be checked after every attempt
+#[derive(Debug, Default, Clone)]
+pub struct ReportedErrors {
+    downloading: Arc<Mutex<Option<String>>>,
+    unpacking: Arc<Mutex<Option<String>>>
+}
+
+impl ReportedErrors {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the error if the update reports one
+    pub fn update(&self, state: &DiffUpdate) {
+        match state {
+            DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingError(err)) => {
+                *self.downloading.lock().unwrap() = Some(err.to_string());
+            }
+
+            DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingError(err)) => {
+                *self.unpacking.lock().unwrap() = Some(err.to_string());
+            }
+
+            _ => ()
+        }
+    }
+
+    /// Get result of the installation attempt for `run`
+    ///
+    /// Downloading errors are returned as `Err` to be retried. Unpacking and other errors
+    /// are returned as `Ok(Err)` to stop the installation. Reported errors are cleared
+    /// for the next attempt
+    pub fn check<E: std::fmt::Display>(&self, result: Result<(), E>) -> anyhow::Result<anyhow::Result<()>> {
+        let downloading = self.downloading.lock().unwrap().take();
+        let unpacking = self.unpacking.lock().unwrap().take();
+
+        if let Some(err) = unpacking {
+            return Ok(Err(anyhow::anyhow!("Failed to unpack files: {err}")));
+        }
+
+        if let Some(err) = downloading {
+            anyhow::bail!("Failed to download files: {err}");
+        }
+
+        Ok(result.map_err(|err| anyhow::anyhow!("{err}")))
+    }
+}
+
+/// Install wine or DXVK build from its `uri`, retrying failed downloads
+///
+/// If all the attempts failed then the same build is searched in the indexes
+/// of the other `components.servers` entries and downloaded from there.
+///
+/// Downloading errors are not sent to the `updater`, instead the last one is returned
+/// when all the attempts are exhausted. Other errors are sent as usual and stop the installation
+pub fn install_component(
+    name: &str,
+    uri: &str,
+    unpack_to: &Path,
+    cancel: &CancellationToken,
+    make_installer: impl Fn(&str) -> anyhow::Result<Installer>,
+    updater: impl Fn(InstallerUpdate) + Clone + Send + 'static
+) -> anyhow::Result<()> {
+    let mut uris = vec![uri.to_string()];
+    let mut mirrors_loaded = false;
+    let mut last_error = None;
+
+    let mut i = 0;
+
+    while i < uris.len() {
+        let uri = uris[i].clone();
+
+        if i > 0 {
+            tracing::warn!("Downloading {name} from the mirror: {uri}");
+        }
+
+        let result = make_installer(&uri).and_then(|mut installer| {
+            run(name, cancel, || {
+                let error = Arc::new(Mutex::new(None));
+
+                installer.install(unpack_to, {
+                    let error = error.clone();
+                    let updater = updater.clone();
+
+                    move |update| {
+                        match update {
+                            InstallerUpdate::DownloadingError(err) => {
+                                *error.lock().unwrap() = Some(err.to_string());
+                            }
+
+                            update => updater(update)
+                        }
+                    }
+                });
+
+                let error = error.lock().unwrap().take();
+
+                match error {
+                    Some(err) => Err(anyhow::anyhow!(err)),
+                    None => Ok(())
+                }
+            })
+        });
+
+        match result {
+            Ok(()) => return Ok(()),
+
+            Err(err) if cancel.is_cancelled() => return Err(err),
+
+            Err(err) => {
+                last_error = Some(err);
+
+                if !mirrors_loaded {
+                    mirrors_loaded = true;
+
+                    for mirror in find_mirrors(name) {
+                        if !uris.contains(&mirror) {
+                            uris.push(mirror);
+                        }
+                    }
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Failed to download {name}")))
+}
+
+/// Find download links of the component in the indexes of all the components servers
+///
+/// Wine and DXVK builds have different names, so both of them are searched
+fn find_mirrors(name: &str) -> Vec<String> {
+    let mut mirrors = Vec::new();
+
+    for (i, host) in CONFIG.components.servers.iter().enumerate() {
+        let path = COMPONENTS_MIRRORS_FOLDER.join(i.to_string());
+
+        if let Err(err) = bandwidth::count_folder(&path, || ComponentsLoader::new(&path).sync(host)) {
+            tracing::warn!("Failed to sync components index with {host}: {err}");
+
+            continue;
+        }
+
+        let uri = wine::Version::find_in(&path, name).ok().flatten()
+            .map(|version| version.uri)
+            .or_else(|| {
+                dxvk::Version::find_in(&path, name).ok().flatten()
+                    .map(|version| version.uri)
+            });
+
+        if let Some(uri) = uri {
+            mirrors.push(uri);
+        }
+    }
+
+    mirrors
+}
+
+fn sleep(delay: Duration, cancel: &CancellationToken) {
+    let until = Instant::now() + delay;
+
+    while Instant::now() < until && !cancel.is_cancelled() {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn backoff_delay() {
+        let base = Duration::from_secs(5);
+
+        assert_eq!(backoff(base, 1), Duration::from_secs(5));
+        assert_eq!(backoff(base, 2), Duration::from_secs(10));
+        assert_eq!(backoff(base, 3), Duration::from_secs(20));
+        assert_eq!(backoff(base, 6), Duration::from_secs(160));
+
+        // Capped by the max delay
+        assert_eq!(backoff(base, 7), MAX_DELAY);
+        assert_eq!(backoff(base, 100), MAX_DELAY);
+        assert_eq!(backoff(Duration::from_secs(600), 1), MAX_DELAY);
+    }
+
+    #[test]
+    fn retry_until_success() {
+        let calls = Cell::new(0);
+
+        let result = run_with("Test", 3, Duration::ZERO, &CancellationToken::new(), || {
+            calls.set(calls.get() + 1);
+
+            if calls.get() < 3 {
+                Err("failed")
+            } else {
+                Ok(calls.get())
+            }
+        });
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn give_up() {
+        let calls = Cell::new(0);
+
+        let result = run_with("Test", 2, Duration::ZERO, &CancellationToken::new(), || {
+            calls.set(calls.get() + 1);
+
+            Err::<(), _>(format!("attempt {}", calls.get()))
+        });
+
+        assert_eq!(result, Err(String::from("attempt 2")));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn cancel_delay() {
+        let cancel = CancellationToken::new();
+        let calls = Cell::new(0);
+
+        let result = run_with("Test", 3, MAX_DELAY, &cancel, || {
+            calls.set(calls.get() + 1);
+
+            cancel.cancel();
+
+            Err::<(), _>("failed")
+        });
+
+        assert_eq!(result, Err("failed"));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn reported_errors() {
+        let errors = ReportedErrors::new();
+
+        assert!(matches!(errors.check(Ok::<_, String>(())), Ok(Ok(()))));
+
+        // Not reported errors are not retried
+        assert!(matches!(errors.check(Err("failed")), Ok(Err(_))));
+
+        // Downloading error is retried even if the installation was finished
+        *errors.downloading.lock().unwrap() = Some(String::from("timed out"));
+
+        assert!(errors.check(Ok::<_, String>(())).is_err());
+
+        // Errors are cleared after the check
+        assert!(matches!(errors.check(Ok::<_, String>(())), Ok(Ok(()))));
+
+        // Unpacking error stops the installation
+        *errors.downloading.lock().unwrap() = Some(String::from("timed out"));
+        *errors.unpacking.lock().unwrap() = Some(String::from("corrupted archive"));
+
+        assert!(matches!(errors.check(Ok::<_, String>(())), Ok(Err(_))));
+    }
+}
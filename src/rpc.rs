@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::*;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Minimal interval between two progress notifications
+///
+/// Start, finish and error events are always sent
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Timeout for writing notifications to a subscribed client
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string()
+        }
+    }
+}
+
+/// Function which performs a method call. Gets method name and its params
+pub type RpcHandler = dyn Fn(&str, &Value) -> Result<Value, RpcError> + Send + Sync;
+
+/// Notifications queue. Each item is a serialized notification line
+static NOTIFICATIONS: OnceLock<Sender<String>> = OnceLock::new();
+
+/// Clients which called the `subscribe` method
+static SUBSCRIBERS: Mutex<Vec<UnixStream>> = Mutex::new(Vec::new());
+
+/// Control socket was created by this process
+static BOUND: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    /// Time of the last sent progress notification of each event
+    static ref LAST_PROGRESS: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+/// Start listening on the control socket in background
+/// 
+/// Every line sent to the socket is a JSON-RPC 2.0 request, and every line
+/// sent back is either a response to a request with an `id`, or a notification
+/// for clients which called the `subscribe` method:
+/// 
+/// ```text
+/// -> {"jsonrpc":"2.0","id":1,"method":"subscribe"}
+/// <- {"jsonrpc":"2.0","id":1,"result":null}
+/// -> {"jsonrpc":"2.0","id":2,"method":"perform_action"}
+/// <- {"jsonrpc":"2.0","id":2,"result":null}
+/// <- {"jsonrpc":"2.0","method":"progress","params":{"event":"downloading_progress","current":1024,"total":4096}}
+/// ```
+///
+/// Subscribed connection gets a write timeout so a client which doesn't read notifications
+/// can't block other ones. The timeout is set for the whole connection, so it applies to
+/// the responses as well. Clients which send long requests should subscribe with a separate connection
+pub fn start(handler: impl Fn(&str, &Value) -> Result<Value, RpcError> + Send + Sync + 'static) -> anyhow::Result<()> {
+    let path = CONTROL_SOCKET_FILE.as_path();
+
+    if path.exists() {
+        // Socket is in use by another launcher instance
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!("Control socket is already used by another process");
+        }
+
+        // Otherwise it's left from the previous run
+        std::fs::remove_file(path)?;
+    }
+
+    let listener = bind(path)?;
+
+    BOUND.store(true, Ordering::Relaxed);
+
+    let (sender, receiver) = mpsc::channel::<String>();
+
+    NOTIFICATIONS.set(sender)
+        .map_err(|_| anyhow::anyhow!("Control socket is already started"))?;
+
+    // Send notifications from a separate thread so slow clients
+    // can't block the thread which produced them
+    std::thread::spawn(move || {
+        while let Ok(line) = receiver.recv() {
+            let mut subscribers = SUBSCRIBERS.lock().unwrap();
+
+            subscribers.retain_mut(|stream| {
+                stream.write_all(line.as_bytes()).is_ok()
+            });
+        }
+    });
+
+    let handler: Arc<RpcHandler> = Arc::new(handler);
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let handler = handler.clone();
+
+                    std::thread::spawn(move || {
+                        if let Err(err) = serve(stream, handler.as_ref()) {
+                            tracing::debug!("Control socket client disconnected: {err}");
+                        }
+                    });
+                }
+
+                Err(err) => tracing::error!("Failed to accept control socket connection: {err}")
+            }
+        }
+    });
+
+    tracing::info!("Listening control socket: {}", path.to_string_lossy());
+
+    Ok(())
+}
+
+/// Bind the socket in a private folder and move it to the `path` when its permissions are set,
+/// so other users can't connect to it in between
+fn bind(path: &Path) -> anyhow::Result<UnixListener> {
+    let file_name = path.file_name()
+        .ok_or_else(|| anyhow::anyhow!("Wrong control socket path"))?
+        .to_string_lossy();
+
+    let folder = path.with_file_name(format!(".{file_name}.{}", std::process::id()));
+    let tmp_path = folder.join(file_name.as_ref());
+
+    // Left from the crashed process with the same id
+    if folder.exists() {
+        std::fs::remove_dir_all(&folder)?;
+    }
+
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&folder)?;
+
+    let result = UnixListener::bind(&tmp_path)
+        .and_then(|listener| {
+            std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&tmp_path, path)?;
+
+            Ok(listener)
+        });
+
+    if let Err(err) = std::fs::remove_dir_all(&folder) {
+        tracing::warn!("Failed to remove control socket folder: {err}");
+    }
+
+    Ok(result?)
+}
+
+/// Remove control socket file if it was created by this process
+pub fn stop() {
+    if BOUND.load(Ordering::Relaxed) && CONTROL_SOCKET_FILE.exists() {
+        if let Err(err) = std::fs::remove_file(CONTROL_SOCKET_FILE.as_path()) {
+            tracing::error!("Failed to remove control socket: {err}");
+        }
+    }
+}
+
+fn serve(stream: UnixStream, handler: &RpcHandler) -> anyhow::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, result) = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => {
+                let id = request.get("id").cloned();
+
+                (id, call(&request, &writer, handler))
+            }
+
+            Err(err) => (Some(Value::Null), Err(RpcError::new(PARSE_ERROR, err)))
+        };
+
+        // Requests without id are notifications and don't need a response
+        let Some(id) = id else {
+            continue;
+        };
+
+        let response = match result {
+            Ok(result) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": result
+            }),
+
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": err.code,
+                    "message": err.message
+                }
+            })
+        };
+
+        writer.write_all(format!("{response}\n").as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn call(request: &Value, stream: &UnixStream, handler: &RpcHandler) -> Result<Value, RpcError> {
+    if request["jsonrpc"].as_str() != Some("2.0") {
+        return Err(RpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported"));
+    }
+
+    let Some(method) = request["method"].as_str() else {
+        return Err(RpcError::new(INVALID_REQUEST, "Method name is not specified"));
+    };
+
+    tracing::debug!("Called control socket method: {method}");
+
+    match method {
+        "subscribe" => {
+            // Applies to the responses of this connection as well
+            let stream = stream.try_clone()
+                .and_then(|stream| {
+                    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+                    Ok(stream)
+                })
+                .map_err(|err| RpcError::new(INTERNAL_ERROR, err))?;
+
+            SUBSCRIBERS.lock().unwrap().push(stream);
+
+            Ok(Value::Null)
+        }
+
+        method => handler(method, &request["params"])
+    }
+}
+
+/// Send notification to all the subscribed clients
+pub fn notify(method: &str, params: Value) {
+    if let Some(sender) = NOTIFICATIONS.get() {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        });
+
+        #[allow(unused_must_use)] {
+            sender.send(format!("{notification}\n"));
+        }
+    }
+}
+
+/// Send `progress` notification made from the installation update
+pub fn notify_progress(state: &DiffUpdate) {
+    let (event, progress) = match state {
+        DiffUpdate::CheckingFreeSpace(_) |
+        DiffUpdate::InstallerUpdate(InstallerUpdate::CheckingFreeSpace(_)) => ("checking_free_space", None),
+
+        DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingStarted(_))         => ("downloading_started", None),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissionsStarted(_)) => ("updating_permissions_started", None),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingStarted(_))           => ("unpacking_started", None),
+
+        DiffUpdate::ApplyingHdiffStarted    => ("applying_hdiff_started", None),
+        DiffUpdate::RemovingOutdatedStarted => ("removing_outdated_started", None),
+
+        DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingProgress(curr, total)) => ("downloading_progress", Some((curr, total))),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissions(curr, total)) => ("updating_permissions_progress", Some((curr, total))),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingProgress(curr, total))   => ("unpacking_progress", Some((curr, total))),
+
+        DiffUpdate::ApplyingHdiffProgress(curr, total)    => ("applying_hdiff_progress", Some((curr, total))),
+        DiffUpdate::RemovingOutdatedProgress(curr, total) => ("removing_outdated_progress", Some((curr, total))),
+
+        DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingFinished)         => ("downloading_finished", None),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UpdatingPermissionsFinished) => ("updating_permissions_finished", None),
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingFinished)           => ("unpacking_finished", None),
+
+        DiffUpdate::ApplyingHdiffFinished    => ("applying_hdiff_finished", None),
+        DiffUpdate::RemovingOutdatedFinished => ("removing_outdated_finished", None),
+
+        DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingError(err)) => {
+            notify("progress", json!({
+                "event": "downloading_error",
+                "error": err.to_string()
+            }));
+
+            return;
+        }
+
+        DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingError(err)) => {
+            notify("progress", json!({
+                "event": "unpacking_error",
+                "error": err
+            }));
+
+            return;
+        }
+    };
+
+    match progress {
+        Some((curr, total)) => notify_progress_values(event, None, *curr, *total),
+
+        None => notify("progress", json!({
+            "event": event
+        }))
+    }
+}
+
+/// Send `progress` notification with raw progress values
+/// 
+/// Too frequent notifications of the same event are skipped, except the last one
+pub fn notify_progress_values(event: &str, caption: Option<&str>, curr: u64, total: u64) {
+    let mut last_progress = LAST_PROGRESS.lock().unwrap();
+
+    if let Some(time) = last_progress.get(event) {
+        if curr < total && time.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+    }
+
+    last_progress.insert(event.to_string(), Instant::now());
+
+    notify("progress", json!({
+        "event": event,
+        "caption": caption,
+        "current": curr,
+        "total": total
+    }));
+}
@@ -0,0 +1,384 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Serialize, Deserialize};
+
+use crate::*;
+
+/// Held while the settings file is being updated
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Launcher settings which are not a part of the SDK config
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub background: BackgroundSettings,
+    pub window: WindowSettings,
+    pub downloads: DownloadSettings,
+    pub notifications: NotificationSettings,
+
+    /// Base URL of the launcher API used to get backgrounds. Can point to a local mirror.
+    /// Official server of the game edition is used if not set
+    pub api_url: Option<String>
+}
+
+impl Settings {
+    /// Load settings from the `SETTINGS_FILE`. Returns default settings if the file doesn't exist
+    pub fn get() -> anyhow::Result<Self> {
+        if !SETTINGS_FILE.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_slice(&std::fs::read(SETTINGS_FILE.as_path())?)?)
+    }
+
+    /// Load settings or use default ones if they can't be read
+    pub fn get_or_default() -> Self {
+        Self::get().unwrap_or_else(|err| {
+            tracing::error!("Failed to read launcher settings: {err}");
+
+            Self::default()
+        })
+    }
+
+    /// Change settings and write them to the `SETTINGS_FILE`. Returns updated settings
+    ///
+    /// The file is read and written under the process-wide lock,
+    /// so concurrent updates of different settings are not lost
+    pub fn update(update: impl FnOnce(&mut Self)) -> anyhow::Result<Self> {
+        let _lock = UPDATE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut settings = Self::get()?;
+
+        update(&mut settings);
+
+        std::fs::write(SETTINGS_FILE.as_path(), serde_json::to_string_pretty(&settings)?)?;
+
+        Ok(settings)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundSource {
+    /// Download the picture from the official API
+    #[default]
+    Official,
+
+    /// Keep current picture without updating it
+    Keep,
+
+    /// Use local picture
+    File,
+
+    /// Use next picture from the local folder on each launcher start
+    Folder,
+
+    /// Download the picture from the custom URL
+    Url
+}
+
+impl BackgroundSource {
+    pub fn list() -> &'static [Self] {
+        &[
+            Self::Official,
+            Self::Keep,
+            Self::File,
+            Self::Folder,
+            Self::Url
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfficialBackground {
+    /// Use the first picture returned by the API
+    #[default]
+    Latest,
+
+    /// Use another picture every day
+    Daily,
+
+    /// Use the picture with given hash
+    Picture(String)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundFocalPoint {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right
+}
+
+impl BackgroundFocalPoint {
+    pub fn list() -> &'static [Self] {
+        &[
+            Self::Center,
+            Self::Top,
+            Self::Bottom,
+            Self::Left,
+            Self::Right
+        ]
+    }
+
+    /// Get CSS `background-position` value which keeps this part of the picture visible
+    pub fn css_position(&self) -> &'static str {
+        match self {
+            Self::Center => "center",
+            Self::Top    => "top",
+            Self::Bottom => "bottom",
+            Self::Left   => "left",
+            Self::Right  => "right"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    pub source: BackgroundSource,
+
+    /// Picture used by the `Official` source
+    pub official: OfficialBackground,
+
+    /// Picture used by the `File` source
+    pub file: Option<PathBuf>,
+
+    /// Folder used by the `Folder` source
+    pub folder: Option<PathBuf>,
+
+    /// Last picture chosen from the `folder`
+    pub folder_current: Option<PathBuf>,
+
+    /// URL used by the `Url` source
+    pub url: String,
+
+    /// Gaussian blur sigma applied to the picture. 0 disables blur
+    pub blur: u32,
+
+    /// Picture darkening in percents
+    pub dim: u32,
+
+    /// Part of the picture which stays visible when it's cropped to the window size
+    pub focal_point: BackgroundFocalPoint
+}
+
+/// Size and maximized state of the main window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool
+}
+
+/// Height and maximized state of the classic style window. Its width is calculated
+/// from the background picture's aspect ratio every time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassicWindowState {
+    pub height: i32,
+    pub maximized: bool
+}
+
+/// Last main window state of each launcher style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub modern: Option<WindowState>,
+    pub classic: Option<ClassicWindowState>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadSettings {
+    /// Download speed limit in KiB/s shared by all the downloads. 0 disables the limit
+    pub speed_limit: u64,
+
+    /// Don't limit download speed from `unlimited_from` to `unlimited_to` hour
+    pub limit_schedule: bool,
+
+    pub unlimited_from: u32,
+    pub unlimited_to: u32,
+
+    /// Amount of the download queue jobs running at the same time
+    pub max_jobs: u32,
+
+    /// Amount of additional attempts made after a failed download
+    pub retries: u32,
+
+    /// Delay before the first retry in seconds. Doubled after each failed attempt
+    pub retry_delay: u64,
+
+    /// Start update predownload in background as soon as it's available
+    pub auto_predownload: bool,
+
+    /// Interval between the background update checks in minutes. 0 disables the checks
+    pub update_check_interval: u64
+}
+
+impl Default for DownloadSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            speed_limit: 0,
+            limit_schedule: false,
+
+            // Unlimited at night
+            unlimited_from: 0,
+            unlimited_to: 7,
+
+            max_jobs: 1,
+
+            retries: 3,
+            retry_delay: 5,
+
+            auto_predownload: false,
+            update_check_interval: 60
+        }
+    }
+}
+
+impl DownloadSettings {
+    /// Get download speed limit in bytes per second at the given hour
+    ///
+    /// Returns `None` if the speed is not limited
+    pub fn speed_limit_at(&self, hour: u32) -> Option<u64> {
+        if self.speed_limit == 0 {
+            return None;
+        }
+
+        if self.limit_schedule {
+            let unlimited = if self.unlimited_from <= self.unlimited_to {
+                (self.unlimited_from..self.unlimited_to).contains(&hour)
+            } else {
+                hour >= self.unlimited_from || hour < self.unlimited_to
+            };
+
+            if unlimited {
+                return None;
+            }
+        }
+
+        Some(self.speed_limit * 1024)
+    }
+}
+
+/// Operations reported with desktop notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    Download,
+    Predownload,
+    Repair,
+    Wine,
+    Prefix
+}
+
+/// Desktop notifications sent when an operation is finished or failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Game and voice packages installation and updating
+    pub downloads: bool,
+    pub predownloads: bool,
+    pub repairs: bool,
+    pub wine: bool,
+    pub prefix: bool
+}
+
+impl Default for NotificationSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            downloads: true,
+            predownloads: true,
+            repairs: true,
+            wine: true,
+            prefix: true
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn enabled(&self, event: NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::Download    => self.downloads,
+            NotificationEvent::Predownload => self.predownloads,
+            NotificationEvent::Repair      => self.repairs,
+            NotificationEvent::Wine        => self.wine,
+            NotificationEvent::Prefix      => self.prefix
+        }
+    }
+
+    pub fn set_enabled(&mut self, event: NotificationEvent, enabled: bool) {
+        match event {
+            NotificationEvent::Download    => self.downloads = enabled,
+            NotificationEvent::Predownload => self.predownloads = enabled,
+            NotificationEvent::Repair      => self.repairs = enabled,
+            NotificationEvent::Wine        => self.wine = enabled,
+            NotificationEvent::Prefix      => self.prefix = enabled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(limit_schedule: bool, unlimited_from: u32, unlimited_to: u32) -> DownloadSettings {
+        DownloadSettings {
+            speed_limit: 100,
+            limit_schedule,
+            unlimited_from,
+            unlimited_to,
+            ..DownloadSettings::default()
+        }
+    }
+
+    #[test]
+    fn no_limit() {
+        let settings = DownloadSettings::default();
+
+        assert!((0..24).all(|hour| settings.speed_limit_at(hour).is_none()));
+    }
+
+    #[test]
+    fn limit_without_schedule() {
+        let settings = settings(false, 0, 7);
+
+        assert!((0..24).all(|hour| settings.speed_limit_at(hour) == Some(100 * 1024)));
+    }
+
+    #[test]
+    fn schedule_within_day() {
+        let settings = settings(true, 9, 17);
+
+        assert_eq!(settings.speed_limit_at(8), Some(100 * 1024));
+        assert_eq!(settings.speed_limit_at(9), None);
+        assert_eq!(settings.speed_limit_at(16), None);
+        assert_eq!(settings.speed_limit_at(17), Some(100 * 1024));
+    }
+
+    #[test]
+    fn schedule_over_midnight() {
+        let settings = settings(true, 22, 7);
+
+        assert_eq!(settings.speed_limit_at(21), Some(100 * 1024));
+        assert_eq!(settings.speed_limit_at(22), None);
+        assert_eq!(settings.speed_limit_at(23), None);
+        assert_eq!(settings.speed_limit_at(0), None);
+        assert_eq!(settings.speed_limit_at(6), None);
+        assert_eq!(settings.speed_limit_at(7), Some(100 * 1024));
+        assert_eq!(settings.speed_limit_at(12), Some(100 * 1024));
+    }
+
+    #[test]
+    fn empty_schedule() {
+        let settings = settings(true, 5, 5);
+
+        assert!((0..24).all(|hour| settings.speed_limit_at(hour) == Some(100 * 1024)));
+    }
+}
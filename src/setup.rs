@@ -0,0 +1,163 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anime_launcher_sdk::components::wine;
+use anime_launcher_sdk::wincompatlib::prelude::*;
+
+use crate::*;
+use crate::cancel::CancellationToken;
+use crate::retry;
+
+/// Wine build which should be used to run the game
+pub enum WineChoice {
+    /// Build which is already downloaded
+    Downloaded(String),
+
+    /// Build which must be downloaded first
+    Download(wine::Version)
+}
+
+/// Choose downloaded wine build, or the selected one (latest if not selected) to download
+pub fn choose_wine(config: &Schema) -> anyhow::Result<WineChoice> {
+    let downloaded = wine::get_downloaded(&config.components.path, &config.game.wine.builds)?;
+
+    if let Some(group) = downloaded.first() {
+        return Ok(WineChoice::Downloaded(group.versions[0].name.clone()));
+    }
+
+    let latest = wine::Version::latest(&config.components.path)?;
+
+    let wine = match &config.game.wine.selected {
+        Some(version) => match wine::Version::find_in(&config.components.path, version) {
+            Ok(Some(version)) => version,
+            _ => latest
+        }
+
+        None => latest
+    };
+
+    Ok(WineChoice::Download(wine))
+}
+
+/// Select wine build used to run the game
+pub fn select_wine(mut config: Schema, name: String) -> anyhow::Result<()> {
+    config.game.wine.selected = Some(name);
+
+    Config::update_raw(config)
+}
+
+/// Download and select the wine build
+///
+/// Failed downloads are retried as described in `retry::install_component`.
+/// Unpacking errors are sent to the `updater` and fail the installation
+pub fn install_wine(config: Schema, wine: &wine::Version, cancel: &CancellationToken, updater: impl Fn(InstallerUpdate) + Clone + Send + 'static) -> anyhow::Result<()> {
+    let make_installer = |uri: &str| -> anyhow::Result<Installer> {
+        let mut installer = Installer::new(uri)?;
+
+        if let Some(temp_folder) = &config.launcher.temp {
+            installer.temp_folder = temp_folder.to_path_buf();
+        }
+
+        Ok(installer)
+    };
+
+    let unpacking_error = Arc::new(Mutex::new(None));
+
+    retry::install_component(&wine.name, &wine.uri, &config.game.wine.builds, cancel, make_installer, {
+        let unpacking_error = unpacking_error.clone();
+
+        move |state| {
+            if let InstallerUpdate::UnpackingError(err) = &state {
+                *unpacking_error.lock().unwrap() = Some(err.clone());
+            }
+
+            updater(state);
+        }
+    })?;
+
+    if let Some(err) = unpacking_error.lock().unwrap().take() {
+        anyhow::bail!("Failed to unpack wine: {err}");
+    }
+
+    select_wine(config, wine.name.clone())
+}
+
+#[derive(Debug)]
+pub enum PrefixError {
+    /// Selected wine build can't be found
+    SelectedWine(Option<anyhow::Error>),
+
+    /// Wine failed to initialize the prefix
+    Init(anyhow::Error)
+}
+
+impl std::fmt::Display for PrefixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SelectedWine(None)      => write!(f, "Failed to get selected wine version"),
+            Self::SelectedWine(Some(err)) => write!(f, "Failed to get selected wine version: {err}"),
+            Self::Init(err)               => write!(f, "Failed to create wine prefix: {err}")
+        }
+    }
+}
+
+impl std::error::Error for PrefixError {}
+
+/// Create wine prefix using the selected wine build
+pub fn create_prefix(config: &Schema) -> Result<(), PrefixError> {
+    let wine = match config.get_selected_wine() {
+        Ok(Some(wine)) => wine,
+        Ok(None) => return Err(PrefixError::SelectedWine(None)),
+        Err(err) => return Err(PrefixError::SelectedWine(Some(err.into())))
+    };
+
+    let wine = wine
+        .to_wine(&config.components.path, Some(config.game.wine.builds.join(&wine.name)))
+        .with_prefix(&config.game.wine.prefix)
+        .with_loader(WineLoader::Current)
+        .with_arch(WineArch::Win64);
+
+    wine.init_prefix(None::<&str>)
+        .map_err(|err| PrefixError::Init(err.into()))?;
+
+    Ok(())
+}
+
+/// Redirect the game's telemetry servers to `0.0.0.0` in the `/etc/hosts` file
+///
+/// Root rights are requested with `pkexec` outside of flatpak,
+/// or if `LAUNCHER_USE_ROOT=1` environment variable is set
+pub fn disable_telemetry(config: &Schema) -> anyhow::Result<()> {
+    let telemetry = config.launcher.edition
+        .telemetry_servers()
+        .iter()
+        .map(|server| format!("echo '0.0.0.0 {server}' >> /etc/hosts"))
+        .collect::<Vec<String>>()
+        .join(" ; ");
+
+    let use_root = std::env::var("LAUNCHER_USE_ROOT")
+        .map(|var| var == "1")
+        .unwrap_or_else(|_| !Path::new("/.flatpak-info").exists());
+
+    let mut command = if use_root {
+        let mut command = Command::new("pkexec");
+
+        command.arg("bash");
+
+        command
+    } else {
+        Command::new("bash")
+    };
+
+    let status = command
+        .arg("-c")
+        .arg(format!("echo '' >> /etc/hosts ; {telemetry} ; echo '' >> /etc/hosts"))
+        .status()?;
+
+    if !status.success() {
+        anyhow::bail!("Failed to update /etc/hosts file");
+    }
+
+    Ok(())
+}
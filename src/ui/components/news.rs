@@ -0,0 +1,135 @@
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use crate::*;
+use crate::news::{Post, PostKind};
+use crate::ui::main::AppMsg;
+
+pub struct NewsPanel {
+    /// Expander row for each posts kind
+    groups: Vec<(PostKind, adw::ExpanderRow)>,
+
+    /// Rows added to the expander rows
+    rows: Vec<(adw::ExpanderRow, adw::ActionRow)>,
+
+    visible: bool
+}
+
+#[derive(Debug)]
+pub enum NewsPanelMsg {
+    SetPosts(Vec<Post>),
+    SetVisible(bool),
+    OpenLink(String)
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for NewsPanel {
+    /// Initial visibility of the panel
+    type Init = bool;
+    type Input = NewsPanelMsg;
+    type Output = AppMsg;
+
+    view! {
+        #[root]
+        adw::PreferencesGroup {
+            set_title: &tr!("news"),
+
+            #[watch]
+            set_visible: model.visible && !model.rows.is_empty()
+        }
+    }
+
+    fn init(init: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = NewsPanel {
+            groups: PostKind::list().iter()
+                .map(|kind| {
+                    let row = adw::ExpanderRow::new();
+
+                    row.set_title(&match kind {
+                        PostKind::Announcement => tr!("news-announcements"),
+                        PostKind::Event        => tr!("news-events"),
+                        PostKind::Info         => tr!("news-info")
+                    });
+
+                    row.set_visible(false);
+
+                    root.add(&row);
+
+                    (*kind, row)
+                })
+                .collect(),
+
+            rows: Vec::new(),
+
+            visible: init
+        };
+
+        let widgets = view_output!();
+
+        std::thread::spawn(move || {
+            match crate::news::get_posts() {
+                Ok(posts) => sender.input(NewsPanelMsg::SetPosts(posts)),
+                Err(err) => tracing::error!("Failed to get news: {err}")
+            }
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            NewsPanelMsg::SetPosts(posts) => {
+                for (group, row) in self.rows.drain(..) {
+                    group.remove(&row);
+                }
+
+                for (kind, group) in &self.groups {
+                    let mut visible = false;
+
+                    for post in posts.iter().filter(|post| post.kind == *kind) {
+                        let row = adw::ActionRow::new();
+
+                        row.set_title(&gtk::glib::markup_escape_text(&post.title));
+                        row.set_subtitle(&gtk::glib::markup_escape_text(&post.date));
+                        row.set_activatable(true);
+
+                        row.add_suffix(&gtk::Image::from_icon_name("adw-external-link-symbolic"));
+
+                        let link = post.link.clone();
+                        let sender = sender.clone();
+
+                        row.connect_activated(move |_| {
+                            sender.input(NewsPanelMsg::OpenLink(link.clone()));
+                        });
+
+                        group.add_row(&row);
+
+                        self.rows.push((group.clone(), row));
+
+                        visible = true;
+                    }
+
+                    group.set_visible(visible);
+                }
+            }
+
+            NewsPanelMsg::SetVisible(visible) => self.visible = visible,
+
+            #[allow(unused_must_use)]
+            NewsPanelMsg::OpenLink(link) => {
+                if !crate::news::is_web_link(&link) {
+                    tracing::warn!("Refused to open news link with unsupported scheme: {link}");
+                }
+
+                else if let Err(err) = open::that(&link) {
+                    tracing::error!("Failed to open news link {link}: {err}");
+
+                    sender.output(AppMsg::Toast {
+                        title: tr!("news-link-opening-error"),
+                        description: Some(err.to_string())
+                    });
+                }
+            }
+        }
+    }
+}
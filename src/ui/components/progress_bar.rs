@@ -15,7 +15,10 @@ pub struct ProgressBarInit {
     /// Add `(XX MB of YY MB)` suffix
     pub display_fraction: bool,
 
-    pub visible: bool
+    pub visible: bool,
+
+    /// Send progress updates to the control socket subscribers
+    pub rpc_notifications: bool
 }
 
 pub struct ProgressBar {
@@ -31,7 +34,10 @@ pub struct ProgressBar {
     /// Add `(XX MB of YY MB)` suffix
     pub display_fraction: bool,
 
-    pub visible: bool
+    pub visible: bool,
+
+    /// Send progress updates to the control socket subscribers
+    pub rpc_notifications: bool
 }
 
 #[derive(Debug)]
@@ -99,7 +105,8 @@ impl SimpleAsyncComponent for ProgressBar {
             downloaded: None,
             display_progress: init.display_progress,
             display_fraction: init.display_fraction,
-            visible: init.visible
+            visible: init.visible,
+            rpc_notifications: init.rpc_notifications
         };
 
         let widgets = view_output!();
@@ -108,6 +115,20 @@ impl SimpleAsyncComponent for ProgressBar {
     }
 
     async fn update(&mut self, msg: Self::Input, _sender: AsyncComponentSender<Self>) {
+        if self.rpc_notifications {
+            match &msg {
+                ProgressBarMsg::UpdateCaption(caption) => crate::rpc::notify("progress", serde_json::json!({
+                    "event": "caption",
+                    "caption": caption
+                })),
+
+                ProgressBarMsg::UpdateProgress(curr, total) => crate::rpc::notify_progress_values("progress", self.caption.as_deref(), *curr, *total),
+                ProgressBarMsg::UpdateFromState(state) => crate::rpc::notify_progress(state),
+
+                _ => ()
+            }
+        }
+
         match msg {
             ProgressBarMsg::Reset => {
                 self.fraction = 0.0;
@@ -0,0 +1,167 @@
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use crate::*;
+use crate::queue::{self, JobId, JobInfo, JobState};
+
+pub struct QueueView {
+    group: adw::PreferencesGroup,
+
+    jobs: Vec<JobInfo>,
+
+    /// Rows of the displayed jobs with their progress bars
+    rows: Vec<(adw::ActionRow, gtk::ProgressBar)>
+}
+
+#[derive(Debug, Clone)]
+pub enum QueueViewMsg {
+    SetJobs(Vec<JobInfo>),
+    Cancel(JobId),
+    Pause(JobId),
+
+    /// Move queued job up (`true`) or down (`false`)
+    Move(JobId, bool)
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for QueueView {
+    type Init = ();
+    type Input = QueueViewMsg;
+    type Output = ();
+
+    view! {
+        #[root]
+        adw::PreferencesGroup {
+            set_title: &tr!("queue"),
+
+            #[watch]
+            set_visible: !model.jobs.is_empty()
+        }
+    }
+
+    fn init(_init: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = QueueView {
+            group: root.clone(),
+
+            jobs: Vec::new(),
+            rows: Vec::new()
+        };
+
+        let widgets = view_output!();
+
+        sender.input(QueueViewMsg::SetJobs(queue::jobs()));
+
+        let input = sender.input_sender().clone();
+
+        queue::subscribe(move |jobs| {
+            input.emit(QueueViewMsg::SetJobs(jobs.to_vec()));
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            QueueViewMsg::SetJobs(jobs) => {
+                let same_jobs = self.jobs.len() == jobs.len() && self.jobs.iter()
+                    .zip(&jobs)
+                    .all(|(old, new)| old.id == new.id && old.state == new.state);
+
+                // Only the progress was changed, so the rows can be kept
+                if same_jobs {
+                    for ((row, progress_bar), job) in self.rows.iter().zip(&jobs) {
+                        row.set_subtitle(&subtitle(job));
+                        progress_bar.set_fraction(fraction(job));
+                    }
+                }
+
+                else {
+                    for (row, _) in self.rows.drain(..) {
+                        self.group.remove(&row);
+                    }
+
+                    for (i, job) in jobs.iter().enumerate() {
+                        let row = adw::ActionRow::new();
+
+                        row.set_title(&gtk::glib::markup_escape_text(&job.title));
+                        row.set_subtitle(&subtitle(job));
+
+                        let progress_bar = gtk::ProgressBar::new();
+
+                        progress_bar.set_valign(gtk::Align::Center);
+                        progress_bar.set_width_request(120);
+                        progress_bar.set_fraction(fraction(job));
+                        progress_bar.set_visible(job.state == JobState::Running);
+
+                        row.add_suffix(&progress_bar);
+
+                        if job.state == JobState::Queued {
+                            // Running jobs are always listed before the queued ones
+                            let first = i == 0 || jobs[i - 1].state == JobState::Running;
+                            let last = i + 1 == jobs.len();
+
+                            row.add_suffix(&button(&sender, "go-up-symbolic", tr!("queue-move-up"), !first, QueueViewMsg::Move(job.id, true)));
+                            row.add_suffix(&button(&sender, "go-down-symbolic", tr!("queue-move-down"), !last, QueueViewMsg::Move(job.id, false)));
+                        }
+
+                        if job.pausable {
+                            row.add_suffix(&button(&sender, "media-playback-pause-symbolic", tr!("pause"), true, QueueViewMsg::Pause(job.id)));
+                        }
+
+                        if job.cancellable {
+                            row.add_suffix(&button(&sender, "process-stop-symbolic", tr!("cancel"), true, QueueViewMsg::Cancel(job.id)));
+                        }
+
+                        self.group.add(&row);
+
+                        self.rows.push((row, progress_bar));
+                    }
+                }
+
+                self.jobs = jobs;
+            }
+
+            QueueViewMsg::Cancel(id) => queue::cancel(id),
+            QueueViewMsg::Pause(id)  => queue::pause(id),
+            QueueViewMsg::Move(id, up) => queue::move_job(id, up)
+        }
+    }
+}
+
+fn subtitle(job: &JobInfo) -> String {
+    match job.state {
+        JobState::Queued => tr!("queued"),
+
+        JobState::Running => {
+            let caption = job.caption.clone().unwrap_or_else(|| tr!("queue-running"));
+
+            match job.progress {
+                (_, 0) => caption,
+
+                (curr, total) => format!("{caption}: {:.2}% ({} of {})", curr as f64 / total as f64 * 100.0, prettify_bytes(curr), prettify_bytes(total))
+            }
+        }
+    }
+}
+
+fn fraction(job: &JobInfo) -> f64 {
+    match job.progress {
+        (_, 0) => 0.0,
+        (curr, total) => curr as f64 / total as f64
+    }
+}
+
+fn button(sender: &ComponentSender<QueueView>, icon: &str, tooltip: String, sensitive: bool, msg: QueueViewMsg) -> gtk::Button {
+    let button = gtk::Button::from_icon_name(icon);
+
+    button.set_valign(gtk::Align::Center);
+    button.set_tooltip_text(Some(&tooltip));
+    button.set_sensitive(sensitive);
+    button.add_css_class("flat");
+
+    let sender = sender.clone();
+
+    button.connect_clicked(move |_| sender.input(msg.clone()));
+
+    button
+}
@@ -13,6 +13,11 @@ use anime_launcher_sdk::anime_game_core::zzz::prelude::*;
 use anime_launcher_sdk::config::ConfigExt;
 use anime_launcher_sdk::zzz::config::Config;
 
+use crate::tr;
+use crate::queue;
+use crate::retry;
+use crate::cancel::{self, CancellationToken};
+
 use super::ComponentGroupMsg;
 use super::progress_bar::ProgressBarMsg;
 
@@ -101,6 +106,7 @@ impl SimpleAsyncComponent for ComponentVersion {
                     display_progress: true,
                     display_fraction: false,
                     visible: false,
+                    rpc_notifications: false
                 })
                 .detach()
         };
@@ -148,50 +154,132 @@ impl SimpleAsyncComponent for ComponentVersion {
 
                     VersionState::NotDownloaded => {
                         if let Ok(config) = Config::get() {
-                            // todo
-                            let mut installer = Installer::new(&self.download_uri)
-                                .expect("Failed to create installer instance for this version")
-                                .with_temp_folder(config.launcher.temp.unwrap_or_else(std::env::temp_dir));
-
-                            if let Some(filename) = &self.download_filename {
-                                installer = installer.with_filename(filename.to_owned());
-                            }
+                            let temp_folder = config.launcher.temp.unwrap_or_else(std::env::temp_dir);
 
                             self.state = VersionState::Downloading;
 
                             let progress_bar_sender = self.progress_bar.sender().clone();
 
+                            #[allow(unused_must_use)] {
+                                progress_bar_sender.send(ProgressBarMsg::Reset);
+                                progress_bar_sender.send(ProgressBarMsg::UpdateCaption(Some(tr!("queued"))));
+                                progress_bar_sender.send(ProgressBarMsg::SetVisible(true));
+                            }
+
+                            let title = tr!("queue-component", {
+                                "component" = self.title.clone()
+                            });
+
+                            let cancel = CancellationToken::new();
+
                             #[allow(unused_must_use)]
-                            std::thread::spawn(clone!(
+                            queue::submit(title, Some(cancel.clone()), clone!(
+                                #[strong(rename_to = name)]
+                                self.name,
+
+                                #[strong(rename_to = download_uri)]
+                                self.download_uri,
+
                                 #[strong(rename_to = download_folder)]
                                 self.download_folder,
 
-                                move || {
-                                    progress_bar_sender.send(ProgressBarMsg::Reset);
-                                    progress_bar_sender.send(ProgressBarMsg::SetVisible(true));
+                                #[strong(rename_to = download_filename)]
+                                self.download_filename,
+
+                                move |job| {
+                                    let result = cancel::run_cancellable(&cancel, clone!(
+                                        #[strong]
+                                        name,
+
+                                        #[strong]
+                                        sender,
+
+                                        #[strong]
+                                        progress_bar_sender,
 
-                                    installer.install(download_folder, move |state| {
-                                        match &state {
-                                            InstallerUpdate::UnpackingFinished |
-                                            InstallerUpdate::DownloadingError(_) |
-                                            InstallerUpdate::UnpackingError(_) => {
-                                                progress_bar_sender.send(ProgressBarMsg::SetVisible(false));
+                                        #[strong]
+                                        cancel,
 
-                                                if let InstallerUpdate::UnpackingFinished = &state {
-                                                    sender.input(ComponentVersionMsg::SetState(VersionState::Downloaded));
-                                                    sender.output(ComponentGroupMsg::CallOnDownloaded);
+                                        move || {
+                                            let throttle = crate::bandwidth::Throttle::new();
+
+                                            let make_installer = |uri: &str| -> anyhow::Result<Installer> {
+                                                let mut installer = Installer::new(uri)?
+                                                    .with_temp_folder(temp_folder.clone());
+
+                                                if let Some(filename) = &download_filename {
+                                                    installer = installer.with_filename(filename.to_owned());
                                                 }
 
-                                                else {
-                                                    sender.input(ComponentVersionMsg::SetState(VersionState::NotDownloaded));
+                                                Ok(installer)
+                                            };
+
+                                            retry::install_component(&name, &download_uri, &download_folder, &cancel, make_installer, clone!(
+                                                #[strong]
+                                                sender,
+
+                                                #[strong]
+                                                progress_bar_sender,
+
+                                                #[strong]
+                                                cancel,
+
+                                                move |state| {
+                                                    match &state {
+                                                        InstallerUpdate::DownloadingProgress(curr, _) => {
+                                                            throttle.update(*curr);
+
+                                                            cancel.stop_if_cancelled();
+                                                        }
+
+                                                        InstallerUpdate::UnpackingFinished |
+                                                        InstallerUpdate::UnpackingError(_) => {
+                                                            progress_bar_sender.send(ProgressBarMsg::SetVisible(false));
+
+                                                            if let InstallerUpdate::UnpackingFinished = &state {
+                                                                sender.input(ComponentVersionMsg::SetState(VersionState::Downloaded));
+                                                                sender.output(ComponentGroupMsg::CallOnDownloaded);
+                                                            }
+
+                                                            else {
+                                                                sender.input(ComponentVersionMsg::SetState(VersionState::NotDownloaded));
+                                                            }
+                                                        },
+
+                                                        _ => ()
+                                                    }
+
+                                                    let state = DiffUpdate::InstallerUpdate(state);
+
+                                                    job.update_from_state(&state);
+
+                                                    progress_bar_sender.send(ProgressBarMsg::UpdateFromState(state));
                                                 }
-                                            },
+                                            ))
+                                        }
+                                    ));
+
+                                    let failed = match result {
+                                        Ok(Ok(())) => false,
+
+                                        Ok(Err(err)) => {
+                                            tracing::error!("Failed to download component {name}: {err}");
 
-                                            _ => ()
+                                            true
                                         }
 
-                                        progress_bar_sender.send(ProgressBarMsg::UpdateFromState(DiffUpdate::InstallerUpdate(state)));
-                                    });
+                                        Err(_) => {
+                                            tracing::warn!("Component downloading was cancelled");
+
+                                            true
+                                        }
+                                    };
+
+                                    if failed {
+                                        progress_bar_sender.send(ProgressBarMsg::SetVisible(false));
+
+                                        sender.input(ComponentVersionMsg::SetState(VersionState::NotDownloaded));
+                                    }
                                 }
                             ));
                         }
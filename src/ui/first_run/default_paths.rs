@@ -265,7 +265,8 @@ impl SimpleAsyncComponent for DefaultPathsApp {
                     caption: None,
                     display_progress: true,
                     display_fraction: false,
-                    visible: false
+                    visible: false,
+                    rpc_notifications: false
                 })
                 .detach(),
 
@@ -68,7 +68,7 @@ impl SimpleAsyncComponent for DependenciesApp {
                         },
 
                         gtk::Entry {
-                            set_text: "sudo pacman -Syu git p7zip libwebp",
+                            set_text: "sudo pacman -Syu git p7zip",
                             set_editable: false
                         }
                     },
@@ -85,7 +85,7 @@ impl SimpleAsyncComponent for DependenciesApp {
                         },
 
                         gtk::Entry {
-                            set_text: "sudo apt install git p7zip-full webp",
+                            set_text: "sudo apt install git p7zip-full",
                             set_editable: false
                         }
                     },
@@ -102,7 +102,7 @@ impl SimpleAsyncComponent for DependenciesApp {
                         },
 
                         gtk::Entry {
-                            set_text: "sudo dnf install git p7zip libwebp-tools",
+                            set_text: "sudo dnf install git p7zip",
                             set_editable: false
                         }
                     },
@@ -121,10 +121,6 @@ impl SimpleAsyncComponent for DependenciesApp {
 
                             adw::ActionRow {
                                 set_title: "p7zip"
-                            },
-
-                            adw::ActionRow {
-                                set_title: "libwebp"
                             }
                         }
                     }
@@ -191,19 +187,15 @@ impl SimpleAsyncComponent for DependenciesApp {
         match msg {
             #[allow(unused_must_use)]
             DependenciesAppMsg::Continue => {
-                let packages = ["git", "dwebp"];
-
-                for package in packages {
-                    if !is_available(package) {
-                        sender.output(Self::Output::Toast {
-                            title: tr!("package-not-available", {
-                                "package" = package
-                            }),
-                            description: None
-                        });
-
-                        return;
-                    }
+                if !is_available("git") {
+                    sender.output(Self::Output::Toast {
+                        title: tr!("package-not-available", {
+                            "package" = "git"
+                        }),
+                        description: None
+                    });
+
+                    return;
                 }
 
                 // 7z sometimes has different binaries
@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use relm4::prelude::*;
 use adw::prelude::*;
 
+use gtk::glib::clone;
+
 use anime_launcher_sdk::anime_game_core::prelude::*;
 use anime_launcher_sdk::wincompatlib::prelude::*;
 
@@ -15,6 +17,10 @@ use anime_launcher_sdk::zzz::config::Config;
 use super::main::FirstRunAppMsg;
 
 use crate::ui::components::*;
+use crate::bandwidth::Throttle;
+use crate::queue;
+use crate::retry;
+use crate::cancel::CancellationToken;
 use crate::*;
 
 fn get_installer(uri: &str, temp: Option<PathBuf>) -> anyhow::Result<Installer> {
@@ -271,7 +277,8 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
                     caption: None,
                     display_progress: true,
                     display_fraction: true,
-                    visible: true
+                    visible: true,
+                    rpc_notifications: false
                 })
                 .detach(),
 
@@ -367,28 +374,38 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
 
                 // Otherwise download wine
                 else {
-                    std::thread::spawn(move || {
+                    let title = tr!("queue-wine", {
+                        "version" = wine.title.clone()
+                    });
+
+                    queue::submit(title, None, move |job| {
                         tracing::info!("Installing wine: {}", wine.name);
 
-                        // Install wine
-                        match get_installer(&wine.uri, config.launcher.temp.clone()) {
-                            Ok(mut installer) => {
-                                // Create wine builds folder
-                                if config.game.wine.builds.exists() {
-                                    std::fs::create_dir_all(&config.game.wine.builds)
-                                        .expect("Failed to create wine builds directory");
-                                }
+                        // Create wine builds folder
+                        if config.game.wine.builds.exists() {
+                            std::fs::create_dir_all(&config.game.wine.builds)
+                                .expect("Failed to create wine builds directory");
+                        }
 
-                                installer.install(&config.game.wine.builds, move |update| {
-                                    match &update {
-                                        InstallerUpdate::DownloadingError(err) => {
-                                            tracing::error!("Failed to download wine: {err}");
+                        let throttle = Throttle::new();
 
-                                            sender.output(Self::Output::Toast {
-                                                title: tr!("wine-download-error"),
-                                                description: Some(err.to_string())
-                                            });
-                                        }
+                        // Install wine
+                        let result = retry::install_component(
+                            &wine.name,
+                            &wine.uri,
+                            &config.game.wine.builds,
+                            &CancellationToken::new(),
+                            |uri| get_installer(uri, config.launcher.temp.clone()),
+                            clone!(
+                                #[strong]
+                                sender,
+
+                                #[strong(rename_to = name)]
+                                wine.name,
+
+                                move |update| {
+                                    match &update {
+                                        InstallerUpdate::DownloadingProgress(curr, _) => throttle.update(*curr),
 
                                         InstallerUpdate::UnpackingError(err) => {
                                             tracing::error!("Failed to unpack wine: {err}");
@@ -403,7 +420,7 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
                                         InstallerUpdate::UnpackingFinished => {
                                             let mut config = Config::get().unwrap_or_else(|_| CONFIG.clone());
 
-                                            config.game.wine.selected = Some(wine.name.clone());
+                                            config.game.wine.selected = Some(name.clone());
 
                                             if let Err(err) = Config::update_raw(config) {
                                                 tracing::error!("Failed to update config: {err}");
@@ -420,18 +437,22 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
                                         _ => ()
                                     }
 
-                                    progress_bar_input.send(ProgressBarMsg::UpdateFromState(DiffUpdate::InstallerUpdate(update)));
-                                });
-                            }
+                                    let update = DiffUpdate::InstallerUpdate(update);
 
-                            Err(err) => {
-                                tracing::error!("Failed to initialize wine installer: {err}");
+                                    job.update_from_state(&update);
 
-                                sender.output(Self::Output::Toast {
-                                    title: tr!("wine-install-failed"),
-                                    description: Some(err.to_string())
-                                });
-                            }
+                                    progress_bar_input.send(ProgressBarMsg::UpdateFromState(update));
+                                }
+                            )
+                        );
+
+                        if let Err(err) = result {
+                            tracing::error!("Failed to install wine: {err}");
+
+                            sender.output(Self::Output::Toast {
+                                title: tr!("wine-download-error"),
+                                description: Some(err.to_string())
+                            });
                         }
                     });
                 }
@@ -490,35 +511,39 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
                 }
 
                 else {
-                    std::thread::spawn(move || {
+                    let title = tr!("queue-component", {
+                        "component" = dxvk.title.clone()
+                    });
+
+                    queue::submit(title, None, move |job| {
                         // Install DXVK
                         tracing::info!("Installing DXVK: {}", dxvk.name);
 
-                        match get_installer(&dxvk.uri, config.launcher.temp.clone()) {
-                            Ok(mut installer) => {
-                                let progress_bar_input = progress_bar_input.clone();
-                                let sender = sender.clone();
+                        // Create DXVK builds folder
+                        if config.game.dxvk.builds.exists() {
+                            std::fs::create_dir_all(&config.game.dxvk.builds)
+                                .expect("Failed to create DXVK builds directory");
+                        }
 
-                                // Create DXVK builds folder
-                                if config.game.dxvk.builds.exists() {
-                                    std::fs::create_dir_all(&config.game.dxvk.builds)
-                                        .expect("Failed to create DXVK builds directory");
-                                }
+                        let throttle = Throttle::new();
 
-                                installer.install(&config.game.dxvk.builds, move |update| {
-                                    match &update {
-                                        InstallerUpdate::DownloadingError(err) => {
-                                            tracing::error!("Failed to download dxvk: {err}");
+                        let result = retry::install_component(
+                            &dxvk.name,
+                            &dxvk.uri,
+                            &config.game.dxvk.builds,
+                            &CancellationToken::new(),
+                            |uri| get_installer(uri, config.launcher.temp.clone()),
+                            clone!(
+                                #[strong]
+                                sender,
 
-                                            sender.output(Self::Output::Toast {
-                                                title: tr!("dxvk-download-error"),
-                                                description: Some(err.to_string())
-                                            });
-                                        }
+                                move |update| {
+                                    match &update {
+                                        InstallerUpdate::DownloadingProgress(curr, _) => throttle.update(*curr),
 
                                         InstallerUpdate::UnpackingError(err) => {
                                             tracing::error!("Failed to unpack dxvk: {err}");
-    
+
                                             sender.output(Self::Output::Toast {
                                                 title: tr!("dxvk-unpack-error"),
                                                 description: Some(err.clone())
@@ -533,18 +558,22 @@ impl SimpleAsyncComponent for DownloadComponentsApp {
                                         _ => ()
                                     }
 
-                                    progress_bar_input.send(ProgressBarMsg::UpdateFromState(DiffUpdate::InstallerUpdate(update)));
-                                });
-                            }
+                                    let update = DiffUpdate::InstallerUpdate(update);
 
-                            Err(err) => {
-                                tracing::error!("Failed to initialize dxvk installer: {err}");
+                                    job.update_from_state(&update);
 
-                                sender.output(Self::Output::Toast {
-                                    title: tr!("dxvk-install-failed"),
-                                    description: Some(err.to_string())
-                                });
-                            }
+                                    progress_bar_input.send(ProgressBarMsg::UpdateFromState(update));
+                                }
+                            )
+                        );
+
+                        if let Err(err) = result {
+                            tracing::error!("Failed to install dxvk: {err}");
+
+                            sender.output(Self::Output::Toast {
+                                title: tr!("dxvk-download-error"),
+                                description: Some(err.to_string())
+                            });
                         }
                     });
                 }
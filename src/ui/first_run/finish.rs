@@ -78,7 +78,9 @@ impl SimpleAsyncComponent for FinishApp {
     async fn update(&mut self, msg: Self::Input, _sender: AsyncComponentSender<Self>) {
         match msg {
             FinishAppMsg::Restart => {
-                std::process::Command::new(std::env::current_exe().unwrap()).spawn().unwrap();
+                // The launcher is started again by the main function after this
+                // instance is closed, otherwise it would be forwarded to this one
+                crate::RESTART.store(true, std::sync::atomic::Ordering::Relaxed);
 
                 relm4::main_application().quit();
             }
@@ -188,6 +188,7 @@ impl SimpleComponent for FirstRunApp {
                 let config = Config::get().unwrap_or_else(|_| CONFIG.clone());
 
                 let components_sender = self.download_components.sender().clone();
+                let components_path = config.components.path.clone();
                 let components = ComponentsLoader::new(config.components.path);
 
                 #[allow(unused_must_use)]
@@ -197,7 +198,7 @@ impl SimpleComponent for FirstRunApp {
 
                         Ok(None) => {
                             for host in &CONFIG.components.servers {
-                                match components.sync(host) {
+                                match bandwidth::count_folder(&components_path, || components.sync(host)) {
                                     Ok(_) => break,
 
                                     Err(err) => {
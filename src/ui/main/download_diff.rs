@@ -1,75 +1,204 @@
-use relm4::{
-    prelude::*,
-    Sender
-};
+use std::sync::Arc;
+
+use relm4::prelude::*;
 
 use gtk::glib::clone;
 
 use crate::*;
-use crate::ui::components::*;
-
-use super::{App, AppMsg};
-
-pub fn download_diff(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>, mut diff: VersionDiff) {
-    sender.input(AppMsg::SetDownloading(true));
-
-    std::thread::spawn(move || {
+use crate::cancel::{self, CancellationToken};
+use crate::journal::{self, DownloadKind, JournalRecorder};
+use crate::integrity::HashIndex;
+use crate::bandwidth::Throttle;
+use crate::queue;
+use crate::retry;
+use crate::settings::NotificationEvent;
+
+use super::{App, AppMsg, QueuedDownload};
+
+/// Cancellation is possible only while downloading, so the partially downloaded
+/// archive is kept in the temp folder and the game files are never left half unpacked.
+/// Downloading archives are recorded to the download journal to resume them later.
+///
+/// The progress is shown in the queue view, where the download can be paused or cancelled
+pub fn download_diff(sender: ComponentSender<App>, mut diff: VersionDiff) {
+    let title = match &diff {
+        VersionDiff::NotInstalled { latest, .. } => tr!("queue-install", {
+            "version" = latest.to_string()
+        }),
+
+        _ => tr!("queue-update", {
+            "version" = diff.latest().to_string()
+        })
+    };
+
+    let cancel = CancellationToken::new();
+
+    queue::submit_pausable(title.clone(), cancel.clone(), move |job| {
         let config = Config::get().unwrap();
         let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
+        let previous_version = crate::integrity::game_version(&game_path);
 
         if let Some(temp) = config.launcher.temp {
             diff = diff.with_temp_folder(temp);
         }
 
-        let result = diff.install_to(game_path, clone!(
+        let checking = std::sync::Once::new();
+
+        journal::prepare_resume(DownloadKind::Update, &cancel, |curr, total| {
+            checking.call_once(|| job.set_caption(tr!("checking-archives")));
+
+            job.set_progress(curr, total);
+        });
+
+        let recorder = Arc::new(JournalRecorder::new(DownloadKind::Update, diff.latest(), journal::game_packages()));
+
+        // Worker is abandoned when the download is cancelled,
+        // and the partially downloaded archive is kept in the temp folder
+        let result = cancel::run_cancellable(&cancel, clone!(
             #[strong]
             sender,
 
-            move |state| {
-                match &state {
-                    DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingError(err)) => {
-                        tracing::error!("Downloading failed: {err}");
+            #[strong]
+            cancel,
+
+            #[strong]
+            recorder,
+
+            #[strong]
+            job,
+
+            #[strong]
+            game_path,
 
-                        sender.input(AppMsg::Toast {
-                            title: tr!("downloading-failed"),
-                            description: Some(err.to_string())
-                        });
-                    }
+            move || {
+                let throttle = Throttle::new();
+                let errors = retry::ReportedErrors::new();
 
-                    DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingError(err)) => {
-                        tracing::error!("Unpacking failed: {err}");
+                retry::run("Game downloading", &cancel, || {
+                    let result = diff.install_to(&game_path, clone!(
+                        #[strong]
+                        sender,
 
-                        sender.input(AppMsg::Toast {
-                            title: tr!("unpacking-failed"),
-                            description: Some(err.clone())
-                        });
-                    }
+                        #[strong]
+                        cancel,
 
-                    _ => ()
-                }
+                        #[strong]
+                        recorder,
 
-                #[allow(unused_must_use)] {
-                    progress_bar_input.send(ProgressBarMsg::UpdateFromState(state));
-                }
+                        #[strong]
+                        throttle,
+
+                        #[strong]
+                        job,
+
+                        #[strong]
+                        errors,
+
+                        move |state| {
+                            match &state {
+                                DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingStarted(target)) => recorder.started(target),
+
+                                DiffUpdate::InstallerUpdate(InstallerUpdate::DownloadingProgress(curr, total)) => {
+                                    throttle.update(*curr);
+                                    recorder.progress(*curr, *total);
+
+                                    cancel.stop_if_cancelled();
+                                }
+
+                                DiffUpdate::InstallerUpdate(InstallerUpdate::UnpackingError(err)) => {
+                                    tracing::error!("Unpacking failed: {err}");
+
+                                    sender.input(AppMsg::Toast {
+                                        title: tr!("unpacking-failed"),
+                                        description: Some(err.clone())
+                                    });
+                                }
+
+                                _ => ()
+                            }
+
+                            // Downloading errors are retried by `retry::run` and reported
+                            // when all the attempts are failed
+                            errors.update(&state);
+
+                            job.update_from_state(&state);
+                        }
+                    ));
+
+                    errors.check(result)
+                }).and_then(|result| result)
             }
         ));
 
         let mut perform_on_download_needed = true;
 
-        if let Err(err) = result {
-            tracing::error!("Downloading failed: {err}");
+        match result {
+            Ok(Ok(())) => {
+                recorder.finished();
+
+                // Verified game files were changed by the update
+                if let Err(err) = HashIndex::update_version(&game_path, previous_version.as_deref()) {
+                    tracing::error!("Failed to update verification index: {err}");
+                }
+
+                sender.input(AppMsg::Notify {
+                    event: NotificationEvent::Download,
+                    title: tr!("download-finished"),
+                    description: Some(title),
+                    success: true
+                });
+
+                // Don't start the next download if the pause was requested while unpacking
+                if cancel.is_cancelled() {
+                    perform_on_download_needed = false;
+                }
+            }
+
+            Ok(Err(err)) => {
+                recorder.flush();
 
-            sender.input(AppMsg::Toast {
-                title: tr!("downloading-failed"),
-                description: Some(err.to_string())
-            });
+                tracing::error!("Downloading failed: {err}");
 
-            // Don't try to download something after state updating
-            // because we just failed to do it
-            perform_on_download_needed = false;
+                sender.input(AppMsg::Toast {
+                    title: tr!("downloading-failed"),
+                    description: Some(err.to_string())
+                });
+
+                sender.input(AppMsg::Notify {
+                    event: NotificationEvent::Download,
+                    title: tr!("downloading-failed"),
+                    description: Some(err.to_string()),
+                    success: false
+                });
+
+                // Don't try to download something after state updating
+                // because we just failed to do it
+                perform_on_download_needed = false;
+            }
+
+            Err(_) => {
+                recorder.flush();
+
+                if cancel.is_paused() {
+                    tracing::info!("Downloading was paused");
+
+                    sender.input(AppMsg::SetPaused(DownloadKind::Update));
+                }
+
+                else {
+                    tracing::warn!("Downloading was cancelled");
+
+                    sender.input(AppMsg::Toast {
+                        title: tr!("operation-cancelled"),
+                        description: None
+                    });
+                }
+
+                perform_on_download_needed = false;
+            }
         }
 
-        sender.input(AppMsg::SetDownloading(false));
+        sender.input(AppMsg::SetQueued(QueuedDownload::Game, false));
         sender.input(AppMsg::UpdateLauncherState {
             perform_on_download_needed,
             show_status_page: false
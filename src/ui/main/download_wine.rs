@@ -1,119 +1,135 @@
-use relm4::{
-    prelude::*,
-    Sender
-};
+use relm4::prelude::*;
 
 use gtk::glib::clone;
 
-use anime_launcher_sdk::components::wine;
-
 use crate::*;
-use crate::ui::components::*;
-
-use super::{App, AppMsg};
-
-pub fn download_wine(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>) {
-    let mut config = Config::get().unwrap();
-
-    match wine::get_downloaded(&CONFIG.components.path, &config.game.wine.builds) {
-        Ok(downloaded) => {
-            // Select downloaded version
-            if !downloaded.is_empty() {
-                config.game.wine.selected = Some(downloaded[0].versions[0].name.clone());
-
-                Config::update(config);
-
-                sender.input(AppMsg::UpdateLauncherState {
-                    perform_on_download_needed: false,
-                    show_status_page: true
+use crate::cancel::{self, CancellationToken};
+use crate::bandwidth::Throttle;
+use crate::queue;
+use crate::setup::{self, WineChoice};
+use crate::settings::NotificationEvent;
+
+use super::{App, AppMsg, QueuedDownload};
+
+/// Select downloaded wine build or download the latest one
+///
+/// The progress is shown in the queue view, where the download can be cancelled
+pub fn download_wine(sender: ComponentSender<App>) {
+    let config = Config::get().unwrap();
+
+    match setup::choose_wine(&config) {
+        // Select downloaded version
+        Ok(WineChoice::Downloaded(name)) => {
+            if let Err(err) = setup::select_wine(config, name) {
+                tracing::error!("Failed to select wine version: {err}");
+
+                sender.input(AppMsg::Toast {
+                    title: tr!("config-update-error"),
+                    description: Some(err.to_string())
                 });
             }
 
-            // Or download new one if none is available
-            else {
-                let latest = wine::Version::latest(&CONFIG.components.path).expect("Failed to get latest wine version");
+            sender.input(AppMsg::SetQueued(QueuedDownload::Wine, false));
+            sender.input(AppMsg::UpdateLauncherState {
+                perform_on_download_needed: false,
+                show_status_page: true
+            });
+        }
 
-                // Choose selected wine version or use latest available one
-                let wine = match &config.game.wine.selected {
-                    Some(version) => match wine::Version::find_in(&config.components.path, version) {
-                        Ok(Some(version)) => version,
-                        _ => latest
-                    }
+        // Or download new one if none is available
+        Ok(WineChoice::Download(wine)) => {
+            let cancel = CancellationToken::new();
 
-                    None => latest
-                };
+            let title = tr!("queue-wine", {
+                "version" = wine.title.clone()
+            });
 
-                // Download wine version
-                match Installer::new(wine.uri) {
-                    Ok(mut installer) => {
-                        if let Some(temp_folder) = &config.launcher.temp {
-                            installer.temp_folder = temp_folder.to_path_buf();
-                        }
+            queue::submit(title, Some(cancel.clone()), clone!(
+                #[strong]
+                sender,
 
-                        sender.input(AppMsg::SetDownloading(true));
+                move |job| {
+                    let title = wine.title.clone();
 
-                        std::thread::spawn(clone!(
-                            #[strong]
-                            sender,
+                    let result = cancel::run_cancellable(&cancel, clone!(
+                        #[strong]
+                        cancel,
 
-                            move || {
-                                installer.install(&config.game.wine.builds, clone!(
-                                    #[strong]
-                                    sender,
+                        move || {
+                            let throttle = Throttle::new();
 
-                                    move |state| {
-                                        match &state {
-                                            InstallerUpdate::DownloadingError(err) => {
-                                                tracing::error!("Downloading failed: {err}");
+                            setup::install_wine(config, &wine, &cancel, clone!(
+                                #[strong]
+                                cancel,
 
-                                                sender.input(AppMsg::Toast {
-                                                    title: tr!("downloading-failed"),
-                                                    description: Some(err.to_string())
-                                                });
-                                            }
+                                move |state| {
+                                    // Only downloading can be interrupted, unpacking is always finished
+                                    if let InstallerUpdate::DownloadingProgress(curr, _) = &state {
+                                        throttle.update(*curr);
 
-                                            InstallerUpdate::UnpackingError(err) => {
-                                                tracing::error!("Unpacking failed: {err}");
+                                        cancel.stop_if_cancelled();
+                                    }
 
-                                                sender.input(AppMsg::Toast {
-                                                    title: tr!("unpacking-failed"),
-                                                    description: Some(err.clone())
-                                                });
-                                            }
+                                    job.update_from_state(&DiffUpdate::InstallerUpdate(state));
+                                }
+                            ))
+                        }
+                    ));
+
+                    match result {
+                        Ok(Ok(())) => {
+                            sender.input(AppMsg::Notify {
+                                event: NotificationEvent::Wine,
+                                title: tr!("wine-installed"),
+                                description: Some(title),
+                                success: true
+                            });
+                        }
 
-                                            _ => ()
-                                        }
+                        Ok(Err(err)) => {
+                            tracing::error!("Failed to install wine: {err}");
 
-                                        #[allow(unused_must_use)] {
-                                            progress_bar_input.send(ProgressBarMsg::UpdateFromState(DiffUpdate::InstallerUpdate(state)));
-                                        }
-                                    }
-                                ));
+                            sender.input(AppMsg::Toast {
+                                title: tr!("wine-install-failed"),
+                                description: Some(err.to_string())
+                            });
 
-                                config.game.wine.selected = Some(wine.name.clone());
+                            sender.input(AppMsg::Notify {
+                                event: NotificationEvent::Wine,
+                                title: tr!("wine-install-failed"),
+                                description: Some(err.to_string()),
+                                success: false
+                            });
+                        }
 
-                                Config::update(config);
+                        Err(_) => {
+                            tracing::warn!("Wine downloading was cancelled");
 
-                                sender.input(AppMsg::SetDownloading(false));
-                                sender.input(AppMsg::UpdateLauncherState {
-                                    perform_on_download_needed: false,
-                                    show_status_page: true
-                                });
-                            }
-                        ));
+                            sender.input(AppMsg::Toast {
+                                title: tr!("operation-cancelled"),
+                                description: None
+                            });
+                        }
                     }
 
-                    Err(err) => sender.input(AppMsg::Toast {
-                        title: tr!("wine-install-failed"),
-                        description: Some(err.to_string())
-                    })
+                    sender.input(AppMsg::SetQueued(QueuedDownload::Wine, false));
+                    sender.input(AppMsg::UpdateLauncherState {
+                        perform_on_download_needed: false,
+                        show_status_page: true
+                    });
                 }
-            }
+            ));
         }
 
-        Err(err) => sender.input(AppMsg::Toast {
-            title: tr!("downloaded-wine-list-failed"),
-            description: Some(err.to_string())
-        })
+        Err(err) => {
+            tracing::error!("Failed to get wine versions: {err}");
+
+            sender.input(AppMsg::Toast {
+                title: tr!("downloaded-wine-list-failed"),
+                description: Some(err.to_string())
+            });
+
+            sender.input(AppMsg::SetQueued(QueuedDownload::Wine, false));
+        }
     }
 }
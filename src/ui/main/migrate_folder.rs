@@ -1,22 +1,65 @@
 use std::path::PathBuf;
 
-use relm4::prelude::*;
+use relm4::{
+    prelude::*,
+    Sender
+};
 
 use crate::*;
+use crate::ui::components::*;
+use crate::cancel::CancellationToken;
 
 use super::{App, AppMsg};
 
-pub fn migrate_folder(sender: ComponentSender<App>, from: PathBuf, to: PathBuf, cleanup_folder: Option<PathBuf>) {
-    sender.input(AppMsg::DisableButtons(true));
+#[allow(unused_must_use)]
+pub fn migrate_folder(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>, from: PathBuf, to: PathBuf, cleanup_folder: Option<PathBuf>, cancel: CancellationToken) {
+    progress_bar_input.send(ProgressBarMsg::Reset);
+    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("migrating-folders"))));
+    progress_bar_input.send(ProgressBarMsg::DisplayProgress(false));
+    progress_bar_input.send(ProgressBarMsg::DisplayFraction(false));
+
+    sender.input(AppMsg::SetBusy(true));
 
     std::thread::spawn(move || {
-        move_files::move_files(&from, &to).expect("Failed to perform migration");
+        match move_files::move_files(&from, &to, &cancel) {
+            Ok(()) => {
+                if let Some(cleanup_folder) = cleanup_folder {
+                    if let Err(err) = std::fs::remove_dir_all(cleanup_folder) {
+                        tracing::error!("Failed to remove cleanup folder: {err}");
+
+                        sender.input(AppMsg::Toast {
+                            title: tr!("folders-migration-failed"),
+                            description: Some(err.to_string())
+                        });
+                    }
+                }
+            }
+
+            // Not moved files stay in the old folder so the migration can be continued later
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted && cancel.is_cancelled() => {
+                tracing::warn!("Folder migration was cancelled");
 
-        if let Some(cleanup_folder) = cleanup_folder {
-            std::fs::remove_dir_all(cleanup_folder).expect("Failed to remove cleanup folder");
+                sender.input(AppMsg::Toast {
+                    title: tr!("operation-cancelled"),
+                    description: None
+                });
+            }
+
+            // Moved files are not restored, so the migration can be continued later as well
+            Err(err) => {
+                tracing::error!("Failed to perform migration: {err}");
+
+                sender.input(AppMsg::Toast {
+                    title: tr!("folders-migration-failed"),
+                    description: Some(err.to_string())
+                });
+            }
         }
 
-        sender.input(AppMsg::DisableButtons(false));
+        progress_bar_input.send(ProgressBarMsg::DisplayProgress(true));
+        progress_bar_input.send(ProgressBarMsg::DisplayFraction(true));
+
+        sender.input(AppMsg::SetBusy(false));
         sender.input(AppMsg::UpdateLauncherState {
             perform_on_download_needed: false,
             show_status_page: true
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use relm4::{
     prelude::*,
     actions::*,
@@ -16,6 +18,10 @@ mod download_diff;
 mod migrate_folder;
 mod disable_telemetry;
 mod launch;
+mod window_state;
+mod resume_download;
+mod predownload;
+mod update_check;
 
 use anime_launcher_sdk::components::loader::ComponentsLoader;
 
@@ -29,9 +35,14 @@ use anime_launcher_sdk::zzz::consts::*;
 
 use crate::*;
 use crate::ui::components::*;
+use crate::rpc::RpcError;
+use crate::cancel::CancellationToken;
+use crate::journal::DownloadKind;
+use crate::settings::NotificationEvent;
 
 use super::preferences::main::*;
 use super::about::*;
+use super::verify_report::*;
 
 relm4::new_action_group!(WindowActionGroup, "win");
 
@@ -45,9 +56,12 @@ relm4::new_stateless_action!(About, WindowActionGroup, "about");
 pub static mut MAIN_WINDOW: Option<adw::ApplicationWindow> = None;
 pub static mut PREFERENCES_WINDOW: Option<AsyncController<PreferencesApp>> = None;
 pub static mut ABOUT_DIALOG: Option<Controller<AboutDialog>> = None;
+pub static mut VERIFY_REPORT_DIALOG: Option<AsyncController<VerifyReportDialog>> = None;
 
 pub struct App {
     progress_bar: AsyncController<ProgressBar>,
+    news: Controller<NewsPanel>,
+    queue: Controller<QueueView>,
 
     toast_overlay: adw::ToastOverlay,
 
@@ -55,10 +69,45 @@ pub struct App {
     style: LauncherStyle,
     state: Option<LauncherState>,
 
-    downloading: bool,
+    /// Repair, verification or folders migration is running.
+    /// Queued downloads don't block the main window
+    busy: bool,
+
     disabled_buttons: bool,
     kill_game_button: bool,
-    disabled_kill_game_button: bool
+    disabled_kill_game_button: bool,
+
+    /// Token of the currently running repair, verification or migration
+    cancellation: Option<CancellationToken>,
+
+    /// Downloads added to the queue from the main window
+    queued: HashSet<QueuedDownload>,
+
+    /// Download paused by the user
+    paused: Option<DownloadKind>,
+
+    /// Interrupted downloads were already checked after the launcher start
+    resume_offered: bool,
+
+    /// Version of the update which was automatically predownloaded in this session
+    auto_predownloaded: Option<String>,
+
+    /// Latest known game version status
+    game_diff: Option<VersionDiff>,
+
+    last_update_check: std::time::Instant,
+
+    /// Game launch requested by another launcher instance before the launcher state was loaded
+    pending_run_game: Option<bool>
+}
+
+/// Download added to the queue from the main window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueuedDownload {
+    /// Game or voice package installation or update
+    Game,
+    Predownload,
+    Wine
 }
 
 #[derive(Debug)]
@@ -73,25 +122,73 @@ pub enum AppMsg {
     },
 
     /// Supposed to be called automatically on app's run when the latest game version
-    /// was retrieved from the API
+    /// was retrieved from the API, and then by the periodic update checks
     SetGameDiff(Option<VersionDiff>),
 
+    /// Look for updates if the update check interval has passed
+    CheckUpdates,
+
     /// Supposed to be called automatically on app's run when the launcher state was chosen
     SetLauncherState(Option<LauncherState>),
 
     SetLauncherStyle(LauncherStyle),
+
+    /// Reload background picture after it or its settings were changed
+    UpdateBackground,
+
     SetLoadingStatus(Option<Option<String>>),
 
-    SetDownloading(bool),
+    SetBusy(bool),
     DisableButtons(bool),
+
+    /// Download was added to the queue (`true`) or finished (`false`)
+    SetQueued(QueuedDownload, bool),
+
+    /// Cancel currently running repair or migration, or forget the paused download
+    Cancel,
+
+    /// Download was stopped after the pause request
+    SetPaused(DownloadKind),
+
+    /// Resume paused download
+    Resume,
+
     SetKillGameButton(bool),
     DisableKillGameButton(bool),
 
     OpenPreferences,
-    RepairGame,
+
+    RepairGame {
+        /// Hash all the game files, even unchanged since their last verification
+        force_full: bool
+    },
+
+    /// Verify game files without repairing them and show the report
+    VerifyGame {
+        /// Hash all the game files, even unchanged since their last verification
+        force_full: bool
+    },
+
+    ShowVerifyReport(crate::integrity::VerifyReport),
+
+    /// Repair files chosen in the verification report
+    RepairFiles {
+        game_path: std::path::PathBuf,
+        files: Vec<crate::integrity::BrokenFile>
+    },
 
     PredownloadUpdate,
+
     PerformAction,
+    KillGame,
+
+    /// Launch the game if it's ready. Sent by another launcher instance
+    /// started with `--run-game` (or `--just-run-game` if `force` is set)
+    /// and by the desktop notifications
+    RunGame {
+        /// Launch the game even if its update can be pre-downloaded
+        force: bool
+    },
 
     HideWindow,
     ShowWindow,
@@ -99,6 +196,14 @@ pub enum AppMsg {
     Toast {
         title: String,
         description: Option<String>
+    },
+
+    /// Send desktop notification if the main window is not focused
+    Notify {
+        event: NotificationEvent,
+        title: String,
+        description: Option<String>,
+        success: bool
     }
 }
 
@@ -127,18 +232,6 @@ impl SimpleComponent for App {
         main_window = adw::ApplicationWindow {
             set_icon_name: Some(APP_ID),
 
-            #[watch]
-            set_default_size: (
-                match model.style {
-                    LauncherStyle::Modern => 900,
-                    LauncherStyle::Classic => 1094 // (w = 1280 / 730 * h, where 1280x730 is default background picture resolution)
-                },
-                match model.style {
-                    LauncherStyle::Modern => 600,
-                    LauncherStyle::Classic => 624
-                }
-            ),
-
             #[watch]
             set_css_classes: &{
                 let mut classes = vec!["background", "csd"];
@@ -238,13 +331,50 @@ impl SimpleComponent for App {
                             },
 
                             #[watch]
-                            set_visible: model.downloading,
+                            set_visible: model.busy || model.paused.is_some(),
 
                             set_vexpand: true,
                             set_margin_top: 48,
                             set_margin_bottom: 48,
 
-                            add = model.progress_bar.widget(),
+                            gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                set_halign: gtk::Align::Center,
+                                set_spacing: 8,
+
+                                append = model.progress_bar.widget(),
+
+                                gtk::Button {
+                                    set_valign: gtk::Align::Center,
+                                    set_tooltip_text: Some(&tr!("resume")),
+
+                                    add_css_class: "circular",
+                                    set_icon_name: "media-playback-start-symbolic",
+
+                                    #[watch]
+                                    set_visible: !model.busy && model.paused.is_some(),
+
+                                    connect_clicked => AppMsg::Resume
+                                },
+
+                                gtk::Button {
+                                    set_valign: gtk::Align::Center,
+                                    set_tooltip_text: Some(&tr!("cancel")),
+
+                                    add_css_class: "circular",
+                                    set_icon_name: "process-stop-symbolic",
+
+                                    #[watch]
+                                    set_visible: model.cancellation.is_some() || model.paused.is_some(),
+
+                                    #[watch]
+                                    set_sensitive: !model.cancellation.as_ref()
+                                        .map(CancellationToken::is_cancelled)
+                                        .unwrap_or_default(),
+
+                                    connect_clicked => AppMsg::Cancel
+                                }
+                            }
                         },
 
                         add = &adw::PreferencesGroup {
@@ -261,7 +391,7 @@ impl SimpleComponent for App {
                             },
 
                             #[watch]
-                            set_visible: !model.downloading,
+                            set_visible: !model.busy && model.paused.is_none(),
 
                             #[watch]
                             set_margin_bottom: match model.style {
@@ -315,7 +445,7 @@ impl SimpleComponent for App {
                                         set_visible: matches!(model.state.as_ref(), Some(LauncherState::PredownloadAvailable { .. })),
 
                                         #[watch]
-                                        set_sensitive: match model.state.as_ref() {
+                                        set_sensitive: !model.queued.contains(&QueuedDownload::Predownload) && match model.state.as_ref() {
                                             Some(LauncherState::PredownloadAvailable { game }) => {
                                                 let config = Config::get().unwrap();
                                                 let temp = config.launcher.temp.unwrap_or_else(std::env::temp_dir);
@@ -426,7 +556,7 @@ impl SimpleComponent for App {
                                         },
 
                                         #[watch]
-                                        set_sensitive: !model.disabled_buttons && match &model.state {
+                                        set_sensitive: !model.disabled_buttons && !model.action_queued() && match &model.state {
                                             Some(LauncherState::GameOutdated { .. }) |
                                             Some(LauncherState::VoiceOutdated(_)) => false,
 
@@ -480,86 +610,7 @@ impl SimpleComponent for App {
                                         set_hexpand: false,
                                         set_width_request: 200,
 
-                                        connect_clicked[sender] => move |_| {
-                                            sender.input(AppMsg::DisableKillGameButton(true));
-
-                                            std::thread::spawn(clone!(
-                                                #[strong]
-                                                sender,
-
-                                                move || {
-                                                    std::thread::sleep(std::time::Duration::from_secs(3));
-
-                                                    sender.input(AppMsg::DisableKillGameButton(false));
-                                                }
-                                            ));
-
-                                            let result = std::process::Command::new("pkill")
-                                                .arg("-f") // full text search
-                                                .arg("-i") // case-insensitive
-                                                .arg("ZenlessZoneZero")
-                                                .spawn();
-
-                                            if let Err(err) = result {
-                                                sender.input(AppMsg::Toast {
-                                                    title: tr!("kill-game-process-failed"),
-                                                    description: Some(err.to_string())
-                                                });
-                                            }
-
-                                            // Old warning message which I don't really understand now:
-                                            //
-                                            // Doesn't work on all the systems
-                                            // e.g. won't work if you didn't install wine system-wide
-                                            // there's some reasons for it
-                                            //
-                                            // UPD: I've tried this, and the problem is that it's completely pointless
-                                            //      For whatever reason it just doesn't work
-
-                                            // match Config::get() {
-                                            //     Ok(config) => {
-                                            //         match config.get_selected_wine() {
-                                            //             Ok(Some(version)) => {
-                                            //                 let result = version
-                                            //                     .to_wine(&config.components.path, Some(&config.game.wine.builds.join(&version.name)))
-                                            //                     .with_prefix(config.get_wine_prefix_path())
-                                            //                     .stop_processes(true);
-
-                                            //                 dbg!(String::from_utf8_lossy(&result.as_ref().ok().unwrap().stdout));
-                                            //                 dbg!(String::from_utf8_lossy(&result.as_ref().ok().unwrap().stderr));
-
-                                            //                 if let Err(err) = result {
-                                            //                     sender.input(AppMsg::Toast {
-                                            //                         title: tr!("kill-game-process-failed"),
-                                            //                         description: Some(err.to_string())
-                                            //                     });
-                                            //                 }
-                                            //             }
-
-                                            //             Ok(None) => {
-                                            //                 sender.input(AppMsg::Toast {
-                                            //                     title: tr!("failed-get-selected-wine"),
-                                            //                     description: None
-                                            //                 });
-                                            //             }
-
-                                            //             Err(err) => {
-                                            //                 sender.input(AppMsg::Toast {
-                                            //                     title: tr!("failed-get-selected-wine"),
-                                            //                     description: Some(err.to_string())
-                                            //                 });
-                                            //             }
-                                            //         }
-                                            //     }
-
-                                            //     Err(err) => {
-                                            //         sender.input(AppMsg::Toast {
-                                            //             title: tr!("config-file-opening-error"),
-                                            //             description: Some(err.to_string())
-                                            //         });
-                                            //     }
-                                            // }
-                                        }
+                                        connect_clicked => AppMsg::KillGame
                                     }
                                 },
 
@@ -579,12 +630,23 @@ impl SimpleComponent for App {
                                     }
                                 }
                             }
-                        }
+                        },
+
+                        add = model.queue.widget(),
+                        add = model.news.widget(),
                     }
                 }
             },
 
-            connect_close_request[sender] => move |_| {
+            connect_close_request[sender] => move |window| {
+                let style = Config::get()
+                    .map(|config| config.launcher.style)
+                    .unwrap_or(CONFIG.launcher.style);
+
+                if let Err(err) = window_state::save(window, style) {
+                    tracing::error!("Failed to save window state: {err}");
+                }
+
                 if let Err(err) = Config::flush() {
                     sender.input(AppMsg::Toast {
                         title: tr!("config-update-error"),
@@ -606,20 +668,41 @@ impl SimpleComponent for App {
                     caption: None,
                     display_progress: true,
                     display_fraction: true,
-                    visible: true
+                    visible: true,
+                    rpc_notifications: true
                 })
                 .detach(),
 
+            news: NewsPanel::builder()
+                .launch(CONFIG.launcher.style == LauncherStyle::Modern)
+                .forward(sender.input_sender(), std::convert::identity),
+
+            queue: QueueView::builder()
+                .launch(())
+                .detach(),
+
             toast_overlay: adw::ToastOverlay::new(),
 
             loading: Some(None),
             style: CONFIG.launcher.style,
             state: None,
 
-            downloading: false,
+            busy: false,
             disabled_buttons: false,
             kill_game_button: false,
-            disabled_kill_game_button: false
+            disabled_kill_game_button: false,
+
+            cancellation: None,
+            queued: HashSet::new(),
+            paused: None,
+            resume_offered: false,
+
+            auto_predownloaded: None,
+
+            game_diff: None,
+            last_update_check: std::time::Instant::now(),
+
+            pending_run_game: None
         };
 
         model.progress_bar.widget().set_halign(gtk::Align::Center);
@@ -629,6 +712,8 @@ impl SimpleComponent for App {
 
         let widgets = view_output!();
 
+        window_state::restore(&widgets.main_window, model.style);
+
         let about_dialog_broker: MessageBroker<AboutDialogMsg> = MessageBroker::new();
 
         unsafe {
@@ -642,6 +727,10 @@ impl SimpleComponent for App {
                 .transient_for(widgets.main_window.clone())
                 .launch_with_broker((), &about_dialog_broker)
                 .detach());
+
+            VERIFY_REPORT_DIALOG = Some(VerifyReportDialog::builder()
+                .launch(widgets.main_window.clone().into())
+                .forward(sender.input_sender(), std::convert::identity));
         }
 
         let mut group = RelmActionGroup::<WindowActionGroup>::new();
@@ -725,9 +814,128 @@ impl SimpleComponent for App {
 
         widgets.main_window.insert_action_group("win", Some(&group.into_action_group()));
 
+        // Launch the game when requested by another launcher instance
+        let run_game = gtk::gio::SimpleAction::new("run-game", Some(gtk::glib::VariantTy::BOOLEAN));
+
+        run_game.connect_activate(clone!(
+            #[strong]
+            sender,
+
+            move |_, force| {
+                sender.input(AppMsg::RunGame {
+                    force: force.and_then(|force| force.get::<bool>()).unwrap_or(false)
+                });
+            }
+        ));
+
+        relm4::main_application().add_action(&run_game);
+
+        // Desktop notifications actions
+        let show_window = gtk::gio::SimpleAction::new("show-window", None);
+
+        show_window.connect_activate(clone!(
+            #[strong]
+            sender,
+
+            move |_, _| sender.input(AppMsg::ShowWindow)
+        ));
+
+        relm4::main_application().add_action(&show_window);
+
+        let show_log = gtk::gio::SimpleAction::new("show-log", None);
+
+        show_log.connect_activate(clone!(
+            #[strong]
+            sender,
+
+            move |_, _| {
+                if let Err(err) = open::that(crate::DEBUG_FILE.as_os_str()) {
+                    sender.input(AppMsg::Toast {
+                        title: tr!("debug-file-opening-error"),
+                        description: Some(err.to_string())
+                    });
+
+                    tracing::error!("Failed to open debug file: {err}");
+                }
+            }
+        ));
+
+        relm4::main_application().add_action(&show_log);
+
+        // Start control socket
+        let input = sender.input_sender().clone();
+
+        let result = crate::rpc::start(move |method, params| {
+            let get_flag = |name: &str, default: bool| match &params[name] {
+                serde_json::Value::Null => Ok(default),
+                serde_json::Value::Bool(value) => Ok(*value),
+
+                _ => Err(RpcError::new(crate::rpc::INVALID_PARAMS, format!("`{name}` must be a boolean")))
+            };
+
+            match method {
+                "get_status" => crate::cli::status::status_json()
+                    .map_err(|err| RpcError::new(crate::rpc::INTERNAL_ERROR, err)),
+
+                "update_launcher_state" => {
+                    input.emit(AppMsg::UpdateLauncherState {
+                        perform_on_download_needed: get_flag("perform_on_download_needed", false)?,
+                        show_status_page: get_flag("show_status_page", true)?
+                    });
+
+                    Ok(serde_json::Value::Null)
+                }
+
+                "perform_action" => {
+                    input.emit(AppMsg::PerformAction);
+
+                    Ok(serde_json::Value::Null)
+                }
+
+                "repair_game" => {
+                    input.emit(AppMsg::RepairGame {
+                        force_full: get_flag("force_full", false)?
+                    });
+
+                    Ok(serde_json::Value::Null)
+                }
+
+                "predownload_update" => {
+                    input.emit(AppMsg::PredownloadUpdate);
+
+                    Ok(serde_json::Value::Null)
+                }
+
+                "kill_game" => {
+                    input.emit(AppMsg::KillGame);
+
+                    Ok(serde_json::Value::Null)
+                }
+
+                _ => Err(RpcError::new(crate::rpc::METHOD_NOT_FOUND, format!("Unknown method: {method}")))
+            }
+        });
+
+        if let Err(err) = result {
+            tracing::error!("Failed to start control socket: {err}");
+        }
+
         tracing::info!("Main window initialized");
 
-        let download_picture = model.style == LauncherStyle::Classic && !KEEP_BACKGROUND_FILE.exists();
+        let download_picture = model.style == LauncherStyle::Classic &&
+            crate::settings::Settings::get_or_default().background.source != crate::settings::BackgroundSource::Keep;
+
+        // Look for updates while the launcher stays open
+        gtk::glib::timeout_add_local(update_check::UPDATE_CHECK_TICK, clone!(
+            #[strong]
+            sender,
+
+            move || {
+                sender.input(AppMsg::CheckUpdates);
+
+                gtk::glib::ControlFlow::Continue
+            }
+        ));
 
         // Initialize some heavy tasks
         std::thread::spawn(move || {
@@ -769,7 +977,7 @@ impl SimpleComponent for App {
 
                         Ok(None) => {
                             for host in &CONFIG.components.servers {
-                                match components.sync(host) {
+                                match bandwidth::count_folder(&CONFIG.components.path, || components.sync(host)) {
                                     Ok(changes) => {
                                         sender.input(AppMsg::Toast {
                                             title: tr!("components-index-updated"),
@@ -916,12 +1124,62 @@ impl SimpleComponent for App {
             }
 
             #[allow(unused_must_use)]
-            AppMsg::SetGameDiff(diff) => unsafe {
-                PREFERENCES_WINDOW.as_ref().unwrap_unchecked().sender().send(PreferencesAppMsg::SetGameDiff(diff));
+            AppMsg::SetGameDiff(diff) => {
+                if let (Some(old), Some(new)) = (&self.game_diff, &diff) {
+                    if let Some(title) = update_check::game_update_found(old, new) {
+                        self.toast(title, None);
+                    }
+                }
+
+                self.game_diff = diff.clone();
+
+                unsafe {
+                    PREFERENCES_WINDOW.as_ref().unwrap_unchecked().sender().send(PreferencesAppMsg::SetGameDiff(diff));
+                }
+            }
+
+            AppMsg::CheckUpdates => {
+                let interval = crate::settings::Settings::get_or_default().downloads.update_check_interval;
+
+                if interval == 0 || self.last_update_check.elapsed() < std::time::Duration::from_secs(interval * 60) {
+                    return;
+                }
+
+                // Try again on the next tick
+                if !crate::READY.load(Ordering::Relaxed) || self.busy || self.disabled_buttons || self.loading.is_some() {
+                    return;
+                }
+
+                self.last_update_check = std::time::Instant::now();
+
+                update_check::check_updates(sender);
             }
 
             AppMsg::SetLauncherState(state) => {
+                if let Some(state) = &state {
+                    crate::rpc::notify("state", crate::cli::status::state_json(state));
+
+                    let auto_predownload = match state {
+                        LauncherState::PredownloadAvailable { game } => self.auto_predownload(&sender, game),
+                        _ => false
+                    };
+
+                    // Offer to resume the update interrupted by the launcher closing.
+                    // Interrupted predownload is continued by the automatic one
+                    if !self.resume_offered {
+                        self.resume_offered = true;
+
+                        if !auto_predownload {
+                            resume_download::offer_resume(&sender, state);
+                        }
+                    }
+                }
+
                 self.state = state;
+
+                if let Some(force) = self.pending_run_game.take() {
+                    sender.input(AppMsg::RunGame { force });
+                }
             }
 
             AppMsg::SetLoadingStatus(status) => {
@@ -929,17 +1187,81 @@ impl SimpleComponent for App {
             }
 
             AppMsg::SetLauncherStyle(style) => {
+                let window = unsafe { MAIN_WINDOW.as_ref().unwrap_unchecked() };
+
+                if let Err(err) = window_state::save(window, self.style) {
+                    tracing::error!("Failed to save window state: {err}");
+                }
+
                 self.style = style;
+
+                window_state::restore(window, style);
+
+                self.news.emit(NewsPanelMsg::SetVisible(style == LauncherStyle::Modern));
             }
 
-            AppMsg::SetDownloading(state) => {
-                self.downloading = state;
+            AppMsg::UpdateBackground => {
+                crate::reload_background_css();
+
+                // Classic style window width depends on the picture's aspect ratio
+                if self.style == LauncherStyle::Classic {
+                    let window = unsafe { MAIN_WINDOW.as_ref().unwrap_unchecked() };
+
+                    if let Err(err) = window_state::save(window, self.style) {
+                        tracing::error!("Failed to save window state: {err}");
+                    }
+
+                    window_state::restore(window, self.style);
+                }
+            }
+
+            AppMsg::SetBusy(state) => {
+                self.busy = state;
+
+                if !state {
+                    self.cancellation = None;
+                }
             }
 
             AppMsg::DisableButtons(state) => {
                 self.disabled_buttons = state;
             }
 
+            AppMsg::SetQueued(download, state) => {
+                if state {
+                    self.queued.insert(download);
+                } else {
+                    self.queued.remove(&download);
+                }
+            }
+
+            AppMsg::Cancel => {
+                if let Some(cancellation) = &self.cancellation {
+                    tracing::info!("Cancelling current operation");
+
+                    cancellation.cancel();
+                }
+
+                // Partially downloaded archives are kept to be resumed from the journal
+                self.paused = None;
+            }
+
+            AppMsg::SetPaused(kind) => {
+                self.paused = Some(kind);
+
+                self.progress_bar.emit(ProgressBarMsg::Reset);
+                self.progress_bar.emit(ProgressBarMsg::UpdateCaption(Some(tr!("download-paused"))));
+            }
+
+            AppMsg::Resume => {
+                match self.paused.take() {
+                    Some(DownloadKind::Update)      => sender.input(AppMsg::PerformAction),
+                    Some(DownloadKind::Predownload) => sender.input(AppMsg::PredownloadUpdate),
+
+                    None => ()
+                }
+            }
+
             AppMsg::SetKillGameButton(state) => {
                 self.kill_game_button = state;
             }
@@ -952,71 +1274,184 @@ impl SimpleComponent for App {
                 PREFERENCES_WINDOW.as_ref().unwrap_unchecked().widget().present();
             }
 
-            AppMsg::RepairGame => repair_game::repair_game(sender, self.progress_bar.sender().to_owned()),
-
-            #[allow(unused_must_use)]
-            AppMsg::PredownloadUpdate => {
-                if let Some(LauncherState::PredownloadAvailable { mut game }) = self.state.clone() {
-                    let tmp = Config::get().unwrap().launcher.temp.unwrap_or_else(std::env::temp_dir);
-
-                    self.downloading = true;
-
-                    let progress_bar_input = self.progress_bar.sender().clone();
+            AppMsg::RepairGame { force_full } => {
+                if !self.busy && !self.queued.contains(&QueuedDownload::Game) {
+                    repair_game::repair_game(sender, self.progress_bar.sender().to_owned(), force_full, self.start_cancellable());
+                }
+            }
 
-                    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("downloading"))));
+            AppMsg::VerifyGame { force_full } => {
+                if !self.busy && !self.queued.contains(&QueuedDownload::Game) {
+                    repair_game::verify_game(sender, self.progress_bar.sender().to_owned(), force_full, self.start_cancellable());
+                }
+            }
 
-                    std::thread::spawn(move || {
-                        let result = game.download_to(&tmp, clone!(
-                            #[strong]
-                            progress_bar_input,
+            AppMsg::ShowVerifyReport(report) => unsafe {
+                VERIFY_REPORT_DIALOG.as_ref()
+                    .unwrap_unchecked()
+                    .emit(VerifyReportDialogMsg::Show(report));
+            }
 
-                            move |curr, total| {
-                                progress_bar_input.send(ProgressBarMsg::UpdateProgress(curr, total));
-                            }
-                        ));
+            AppMsg::RepairFiles { game_path, files } => {
+                if !self.busy && !self.queued.contains(&QueuedDownload::Game) {
+                    repair_game::repair_files(sender, self.progress_bar.sender().to_owned(), game_path, files, self.start_cancellable());
+                }
+            }
 
-                        if let Err(err) = result {
-                            sender.input(AppMsg::Toast {
-                                title: tr!("downloading-failed"),
-                                description: Some(err.to_string())
-                            });
+            AppMsg::PredownloadUpdate => {
+                if self.busy || self.queued.contains(&QueuedDownload::Predownload) {
+                    return;
+                }
 
-                            tracing::error!("Failed to predownload update: {err}");
-                        }
+                if let Some(LauncherState::PredownloadAvailable { game }) = self.state.clone() {
+                    self.start_download(QueuedDownload::Predownload);
 
-                        sender.input(AppMsg::SetDownloading(false));
-                        sender.input(AppMsg::UpdateLauncherState {
-                            perform_on_download_needed: false,
-                            show_status_page: true
-                        });
-                    });
+                    predownload::predownload(sender, game);
                 }
             }
 
-            AppMsg::PerformAction => unsafe {
-                match self.state.as_ref().unwrap_unchecked() {
+            AppMsg::PerformAction => {
+                // Can be called from the control socket while another action is running
+                if self.busy || self.disabled_buttons || self.action_queued() {
+                    return;
+                }
+
+                let Some(state) = self.state.clone() else {
+                    return;
+                };
+
+                match state {
                     LauncherState::PredownloadAvailable { .. } |
                     LauncherState::Launch => launch::launch(sender),
 
                     LauncherState::FolderMigrationRequired { from, to, cleanup_folder } =>
-                        migrate_folder::migrate_folder(sender, from.to_owned(), to.to_owned(), cleanup_folder.to_owned()),
+                        migrate_folder::migrate_folder(sender, self.progress_bar.sender().to_owned(), from, to, cleanup_folder, self.start_cancellable()),
 
                     LauncherState::TelemetryNotDisabled => disable_telemetry::disable_telemetry(sender),
 
-                    LauncherState::WineNotInstalled => download_wine::download_wine(sender, self.progress_bar.sender().to_owned()),
-                    LauncherState::PrefixNotExists  => create_prefix::create_prefix(sender),
+                    LauncherState::WineNotInstalled => {
+                        self.start_download(QueuedDownload::Wine);
+
+                        download_wine::download_wine(sender);
+                    }
+
+                    LauncherState::PrefixNotExists => create_prefix::create_prefix(sender),
 
                     LauncherState::GameUpdateAvailable(diff) |
                     LauncherState::GameNotInstalled(diff) |
                     LauncherState::VoiceUpdateAvailable(diff) |
-                    LauncherState::VoiceNotInstalled(diff) =>
-                        download_diff::download_diff(sender, self.progress_bar.sender().to_owned(), diff.to_owned()),
+                    LauncherState::VoiceNotInstalled(diff) => {
+                        self.start_download(QueuedDownload::Game);
+
+                        download_diff::download_diff(sender, diff);
+                    }
 
                     LauncherState::GameOutdated(_) |
                     LauncherState::VoiceOutdated(_) => ()
                 }
             }
 
+            AppMsg::RunGame { force } => {
+                // Wait until the launcher state is loaded
+                if self.state.is_none() || self.loading.is_some() {
+                    self.pending_run_game = Some(force);
+
+                    return;
+                }
+
+                if self.busy || self.disabled_buttons {
+                    return;
+                }
+
+                match &self.state {
+                    Some(LauncherState::Launch) => launch::launch(sender),
+                    Some(LauncherState::PredownloadAvailable { .. }) if force => launch::launch(sender),
+
+                    _ => ()
+                }
+            }
+
+            AppMsg::KillGame => {
+                self.disabled_kill_game_button = true;
+
+                std::thread::spawn(clone!(
+                    #[strong]
+                    sender,
+
+                    move || {
+                        std::thread::sleep(std::time::Duration::from_secs(3));
+
+                        sender.input(AppMsg::DisableKillGameButton(false));
+                    }
+                ));
+
+                let result = std::process::Command::new("pkill")
+                    .arg("-f") // full text search
+                    .arg("-i") // case-insensitive
+                    .arg("ZenlessZoneZero")
+                    .spawn();
+
+                if let Err(err) = result {
+                    sender.input(AppMsg::Toast {
+                        title: tr!("kill-game-process-failed"),
+                        description: Some(err.to_string())
+                    });
+                }
+
+                // Old warning message which I don't really understand now:
+                //
+                // Doesn't work on all the systems
+                // e.g. won't work if you didn't install wine system-wide
+                // there's some reasons for it
+                //
+                // UPD: I've tried this, and the problem is that it's completely pointless
+                //      For whatever reason it just doesn't work
+
+                // match Config::get() {
+                //     Ok(config) => {
+                //         match config.get_selected_wine() {
+                //             Ok(Some(version)) => {
+                //                 let result = version
+                //                     .to_wine(&config.components.path, Some(&config.game.wine.builds.join(&version.name)))
+                //                     .with_prefix(config.get_wine_prefix_path())
+                //                     .stop_processes(true);
+
+                //                 dbg!(String::from_utf8_lossy(&result.as_ref().ok().unwrap().stdout));
+                //                 dbg!(String::from_utf8_lossy(&result.as_ref().ok().unwrap().stderr));
+
+                //                 if let Err(err) = result {
+                //                     sender.input(AppMsg::Toast {
+                //                         title: tr!("kill-game-process-failed"),
+                //                         description: Some(err.to_string())
+                //                     });
+                //                 }
+                //             }
+
+                //             Ok(None) => {
+                //                 sender.input(AppMsg::Toast {
+                //                     title: tr!("failed-get-selected-wine"),
+                //                     description: None
+                //                 });
+                //             }
+
+                //             Err(err) => {
+                //                 sender.input(AppMsg::Toast {
+                //                     title: tr!("failed-get-selected-wine"),
+                //                     description: Some(err.to_string())
+                //                 });
+                //             }
+                //         }
+                //     }
+
+                //     Err(err) => {
+                //         sender.input(AppMsg::Toast {
+                //             title: tr!("config-file-opening-error"),
+                //             description: Some(err.to_string())
+                //         });
+                //     }
+                // }
+            }
+
             AppMsg::HideWindow => unsafe {
                 MAIN_WINDOW.as_ref().unwrap_unchecked().set_visible(false);
             }
@@ -1025,12 +1460,95 @@ impl SimpleComponent for App {
                 MAIN_WINDOW.as_ref().unwrap_unchecked().present();
             }
 
-            AppMsg::Toast { title, description } => self.toast(title, description)
+            AppMsg::Toast { title, description } => self.toast(title, description),
+
+            AppMsg::Notify { event, title, description, success } => {
+                let window = unsafe { MAIN_WINDOW.as_ref().unwrap_unchecked() };
+
+                // Toasts are already visible in the focused window
+                if !window.is_active() {
+                    crate::notifications::send(event, &title, description.as_deref(), success);
+                }
+            }
         }
     }
 }
 
 impl App {
+    /// Create cancellation token for the operation which is about to start
+    fn start_cancellable(&mut self) -> CancellationToken {
+        let cancellation = CancellationToken::new();
+
+        self.cancellation = Some(cancellation.clone());
+        self.paused = None;
+
+        cancellation
+    }
+
+    /// Start background predownload of the update if it's enabled in the download settings
+    ///
+    /// Every update is predownloaded automatically only once per session,
+    /// so it's not restarted after being cancelled. Returns `true` if the predownload was started
+    fn auto_predownload(&mut self, sender: &ComponentSender<Self>, game: &VersionDiff) -> bool {
+        let version = game.latest().to_string();
+
+        if self.busy || self.queued.contains(&QueuedDownload::Predownload) || self.auto_predownloaded.as_ref() == Some(&version) {
+            return false;
+        }
+
+        if !crate::settings::Settings::get_or_default().downloads.auto_predownload || predownload::is_downloaded(game) {
+            return false;
+        }
+
+        self.auto_predownloaded = Some(version.clone());
+
+        if !predownload::enough_free_space(game) {
+            tracing::warn!("Not enough free space to predownload {version} update");
+
+            sender.input(AppMsg::Toast {
+                title: tr!("predownload-no-free-space", {
+                    "version" = version
+                }),
+                description: None
+            });
+
+            return false;
+        }
+
+        tracing::info!("Predownloading {version} update automatically");
+
+        self.start_download(QueuedDownload::Predownload);
+
+        predownload::predownload(sender.clone(), game.clone());
+
+        true
+    }
+
+    /// Remember the download which is about to be queued so it's not queued twice.
+    /// Download function must send `AppMsg::SetQueued(download, false)` when it's finished
+    fn start_download(&mut self, download: QueuedDownload) {
+        self.queued.insert(download);
+
+        // Paused download is continued by the queued one
+        self.paused = None;
+    }
+
+    /// Download required by the current launcher state is already queued
+    fn action_queued(&self) -> bool {
+        let download = match &self.state {
+            Some(LauncherState::WineNotInstalled) => QueuedDownload::Wine,
+
+            Some(LauncherState::GameUpdateAvailable(_)) |
+            Some(LauncherState::GameNotInstalled(_)) |
+            Some(LauncherState::VoiceUpdateAvailable(_)) |
+            Some(LauncherState::VoiceNotInstalled(_)) => QueuedDownload::Game,
+
+            _ => return false
+        };
+
+        self.queued.contains(&download)
+    }
+
     pub fn toast<T: AsRef<str>>(&mut self, title: T, description: Option<T>) {
         let toast = adw::Toast::new(title.as_ref());
 
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use relm4::prelude::*;
+
+use gtk::prelude::*;
+use gtk::glib::clone;
+
+use crate::*;
+use crate::cancel::{self, CancellationToken};
+use crate::journal::{self, DownloadKind, JournalRecorder};
+use crate::bandwidth::Throttle;
+use crate::queue;
+use crate::retry;
+use crate::settings::NotificationEvent;
+
+use super::{App, AppMsg, QueuedDownload};
+
+/// Download the game update in advance
+///
+/// The update is downloaded in background: the main window stays available to launch the game
+/// and the progress is shown in the queue view, where the download can be paused or cancelled
+pub fn predownload(sender: ComponentSender<App>, mut game: VersionDiff) {
+    let tmp = temp_folder();
+    let cancel = CancellationToken::new();
+
+    let title = tr!("queue-predownload", {
+        "version" = game.latest().to_string()
+    });
+
+    queue::submit_pausable(title, cancel.clone(), move |job| {
+        let checking = std::sync::Once::new();
+
+        journal::prepare_resume(DownloadKind::Predownload, &cancel, |curr, total| {
+            checking.call_once(|| job.set_caption(tr!("checking-archives")));
+
+            job.set_progress(curr, total);
+        });
+
+        job.set_caption(tr!("downloading"));
+
+        let recorder = Arc::new(JournalRecorder::new(DownloadKind::Predownload, game.latest(), journal::game_packages()));
+
+        if let Some(file_name) = game.file_name() {
+            recorder.started(&tmp.join(file_name));
+        }
+
+        let version = game.latest().to_string();
+
+        // Worker is abandoned when the download is cancelled,
+        // and the partially downloaded update is kept in the temp folder
+        let result = cancel::run_cancellable(&cancel, clone!(
+            #[strong]
+            cancel,
+
+            #[strong]
+            recorder,
+
+            #[strong]
+            job,
+
+            move || {
+                let throttle = Throttle::new();
+
+                retry::run("Update predownloading", &cancel, || game.download_to(&tmp, clone!(
+                    #[strong]
+                    cancel,
+
+                    #[strong]
+                    recorder,
+
+                    #[strong]
+                    throttle,
+
+                    #[strong]
+                    job,
+
+                    move |curr, total| {
+                        throttle.update(curr);
+                        recorder.progress(curr, total);
+
+                        cancel.stop_if_cancelled();
+
+                        job.set_progress(curr, total);
+                    }
+                ))).map_err(|err| anyhow::anyhow!("{err}"))
+            }
+        ));
+
+        match result {
+            Ok(Ok(_)) => {
+                recorder.finished();
+
+                let title = tr!("predownload-finished", {
+                    "version" = version
+                });
+
+                sender.input(AppMsg::Toast {
+                    title: title.clone(),
+                    description: None
+                });
+
+                sender.input(AppMsg::Notify {
+                    event: NotificationEvent::Predownload,
+                    title,
+                    description: None,
+                    success: true
+                });
+            }
+
+            Ok(Err(err)) => {
+                recorder.flush();
+
+                sender.input(AppMsg::Toast {
+                    title: tr!("downloading-failed"),
+                    description: Some(err.to_string())
+                });
+
+                sender.input(AppMsg::Notify {
+                    event: NotificationEvent::Predownload,
+                    title: tr!("downloading-failed"),
+                    description: Some(err.to_string()),
+                    success: false
+                });
+
+                tracing::error!("Failed to predownload update: {err}");
+            }
+
+            Err(_) => {
+                recorder.flush();
+
+                if cancel.is_paused() {
+                    tracing::info!("Update predownloading was paused");
+
+                    sender.input(AppMsg::SetPaused(DownloadKind::Predownload));
+                }
+
+                else {
+                    tracing::warn!("Update predownloading was cancelled");
+
+                    sender.input(AppMsg::Toast {
+                        title: tr!("operation-cancelled"),
+                        description: None
+                    });
+                }
+            }
+        }
+
+        sender.input(AppMsg::SetQueued(QueuedDownload::Predownload, false));
+        sender.input(AppMsg::UpdateLauncherState {
+            perform_on_download_needed: false,
+            show_status_page: false
+        });
+    });
+}
+
+/// Check if the update is already predownloaded
+pub fn is_downloaded(game: &VersionDiff) -> bool {
+    Some(downloaded_size(game)) >= game.downloaded_size()
+}
+
+/// Check if there's enough free space in the temp folder to predownload the update
+pub fn enough_free_space(game: &VersionDiff) -> bool {
+    let Some(size) = game.downloaded_size() else {
+        return true;
+    };
+
+    match free_space(&temp_folder()) {
+        // Partially downloaded archive is continued
+        Some(free) => free >= size.saturating_sub(downloaded_size(game)),
+
+        None => true
+    }
+}
+
+fn temp_folder() -> PathBuf {
+    Config::get()
+        .map(|config| config.launcher.temp.unwrap_or_else(std::env::temp_dir))
+        .unwrap_or_else(|_| std::env::temp_dir())
+}
+
+fn downloaded_size(game: &VersionDiff) -> u64 {
+    game.file_name()
+        .and_then(|file_name| temp_folder().join(file_name).metadata().ok())
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+fn free_space(path: &Path) -> Option<u64> {
+    gtk::gio::File::for_path(path)
+        .query_filesystem_info("filesystem::free", gtk::gio::Cancellable::NONE)
+        .map(|info| info.attribute_uint64("filesystem::free"))
+        .ok()
+}
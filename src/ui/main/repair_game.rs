@@ -1,132 +1,198 @@
+use std::path::{Path, PathBuf};
+
 use relm4::{
     prelude::*,
     Sender
 };
 
-use gtk::glib::clone;
-
 use crate::*;
 use crate::ui::components::*;
+use crate::integrity::{BrokenFile, VerifyReport};
+use crate::cancel::CancellationToken;
+use crate::settings::NotificationEvent;
 
 use super::{App, AppMsg};
 
+/// Verify game files. Must be called from a separate thread
+///
+/// Returns `None` if integrity files can't be fetched
 #[allow(unused_must_use)]
-pub fn repair_game(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>) {
+fn verify_files(sender: &ComponentSender<App>, progress_bar_input: &Sender<ProgressBarMsg>, force_full: bool, cancel: &CancellationToken) -> Option<VerifyReport> {
     let config = Config::get().unwrap();
 
-    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("verifying-files"))));
-    sender.input(AppMsg::SetDownloading(true));
+    match repairer::try_get_integrity_files(config.launcher.edition, None) {
+        Ok(files) => {
+            let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
 
-    std::thread::spawn(move || {
-        match repairer::try_get_integrity_files(config.launcher.edition, None) {
-            Ok(files) => {
-                let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
+            let options = integrity::VerifyOptions::from_config(&config, force_full);
+
+            let report = integrity::verify(files, &game_path, options, cancel, |state| {
+                let caption = match &state.current_file {
+                    Some(file) => tr!("verifying-file", {
+                        "file" = file.file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
 
-                progress_bar_input.send(ProgressBarMsg::UpdateProgress(0, 0));
+                        "speed" = prettify_bytes(state.speed)
+                    }),
 
-                let mut total = 0;
+                    None => tr!("verifying-files")
+                };
 
-                for file in &files {
-                    total += file.size;
-                }
+                progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(caption)));
+                progress_bar_input.send(ProgressBarMsg::UpdateProgress(state.processed, state.total));
+            });
 
-                let median_size = total / config.launcher.repairer.threads;
-                let mut i = 0;
+            if !report.broken.is_empty() {
+                tracing::warn!("Found broken files:\n{}", report.broken.iter().fold(String::new(), |acc, file| acc + &format!("- {}\n", file.path.to_string_lossy())));
+            }
 
-                let (verify_sender, verify_receiver) = std::sync::mpsc::channel();
+            Some(report)
+        }
 
-                for _ in 0..config.launcher.repairer.threads {
-                    let mut thread_files = Vec::new();
-                    let mut thread_files_size = 0;
+        Err(err) => {
+            tracing::error!("Failed to get inregrity failes: {err}");
 
-                    while i < files.len() {
-                        thread_files.push(files[i].clone());
+            sender.input(AppMsg::Toast {
+                title: tr!("integrity-files-getting-error"),
+                description: Some(err.to_string())
+            });
 
-                        thread_files_size += files[i].size;
-                        i += 1;
+            None
+        }
+    }
+}
 
-                        if thread_files_size >= median_size {
-                            break;
-                        }
-                    }
+/// Repair given game files. Must be called from a separate thread
+///
+/// File being repaired is always finished, so cancellation stops before the next one.
+/// Desktop notification is sent unless the repairing was cancelled
+#[allow(unused_must_use)]
+fn repair_broken_files(sender: &ComponentSender<App>, progress_bar_input: &Sender<ProgressBarMsg>, game_path: &Path, broken: Vec<BrokenFile>, cancel: &CancellationToken) {
+    if broken.is_empty() {
+        notify_repaired(sender, 0);
 
-                    let thread_sender = verify_sender.clone();
+        return;
+    }
 
-                    std::thread::spawn(clone!(
-                        #[strong]
-                        game_path, 
+    let total = broken.len() as u64;
 
-                        move || {
-                            for file in thread_files {
-                                let status = if config.launcher.repairer.fast {
-                                    file.fast_verify(&game_path)
-                                } else {
-                                    file.verify(&game_path)
-                                };
+    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("repairing-files"))));
+    progress_bar_input.send(ProgressBarMsg::DisplayFraction(false));
+    progress_bar_input.send(ProgressBarMsg::UpdateProgress(0, total));
 
-                                thread_sender.send((file, status)).unwrap();
-                            }
-                        }
-                    ));
-                }
+    let mut failed = 0;
 
-                // We have [config.launcher.repairer.threads] copies of this sender + the original one
-                // receiver will return Err when all the senders will be dropped.
-                // [config.launcher.repairer.threads] senders will be dropped when threads will finish verifying files
-                // but this one will live as long as current thread exists so we should drop it manually
-                drop(verify_sender);
+    for (i, file) in broken.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            tracing::warn!("Game files repairing was cancelled after repairing {i} files");
 
-                let mut broken = Vec::new();
-                let mut processed = 0;
+            sender.input(AppMsg::Toast {
+                title: tr!("operation-cancelled"),
+                description: None
+            });
 
-                while let Ok((file, status)) = verify_receiver.recv() {
-                    processed += file.size;
+            progress_bar_input.send(ProgressBarMsg::DisplayFraction(true));
 
-                    if !status {
-                        broken.push(file);
-                    }
+            return;
+        }
 
-                    progress_bar_input.send(ProgressBarMsg::UpdateProgress(processed, total));
-                }
+        tracing::debug!("Repairing file: {}", file.path.to_string_lossy());
 
-                if !broken.is_empty() {
-                    let total = broken.len() as u64;
+        if let Err(err) = file.repair(game_path) {
+            sender.input(AppMsg::Toast {
+                title: tr!("game-file-repairing-error"),
+                description: Some(err.to_string())
+            });
 
-                    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("repairing-files"))));
-                    progress_bar_input.send(ProgressBarMsg::DisplayFraction(false));
-                    progress_bar_input.send(ProgressBarMsg::UpdateProgress(0, total));
+            tracing::error!("Failed to repair game file: {err}");
 
-                    tracing::warn!("Found broken files:\n{}", broken.iter().fold(String::new(), |acc, file| acc + &format!("- {}\n", file.path.to_string_lossy())));
+            failed += 1;
+        }
 
-                    for (i, file) in broken.into_iter().enumerate() {
-                        tracing::debug!("Repairing file: {}", file.path.to_string_lossy());
+        progress_bar_input.send(ProgressBarMsg::UpdateProgress(i as u64 + 1, total));
+    }
 
-                        if let Err(err) = file.repair(&game_path) {
-                            sender.input(AppMsg::Toast {
-                                title: tr!("game-file-repairing-error"),
-                                description: Some(err.to_string())
-                            });
+    progress_bar_input.send(ProgressBarMsg::DisplayFraction(true));
 
-                            tracing::error!("Failed to repair game file: {err}");
-                        }
+    notify_repaired(sender, failed);
+}
 
-                        progress_bar_input.send(ProgressBarMsg::UpdateProgress(i as u64 + 1, total));
-                    }
+fn notify_repaired(sender: &ComponentSender<App>, failed: u64) {
+    sender.input(if failed == 0 {
+        AppMsg::Notify {
+            event: NotificationEvent::Repair,
+            title: tr!("game-files-repaired"),
+            description: None,
+            success: true
+        }
+    } else {
+        AppMsg::Notify {
+            event: NotificationEvent::Repair,
+            title: tr!("game-files-repairing-failed", {
+                "files" = failed
+            }),
+            description: None,
+            success: false
+        }
+    });
+}
 
-                    progress_bar_input.send(ProgressBarMsg::DisplayFraction(true));
-                }
-            }
+/// Verify game files and repair all the broken ones
+///
+/// If the verification was cancelled then the report is shown instead
+#[allow(unused_must_use)]
+pub fn repair_game(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>, force_full: bool, cancel: CancellationToken) {
+    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("verifying-files"))));
+    sender.input(AppMsg::SetBusy(true));
 
-            Err(err) => {
-                tracing::error!("Failed to get inregrity failes: {err}");
+    std::thread::spawn(move || {
+        if let Some(report) = verify_files(&sender, &progress_bar_input, force_full, &cancel) {
+            if report.cancelled {
+                sender.input(AppMsg::ShowVerifyReport(report));
+            }
 
-                sender.input(AppMsg::Toast {
-                    title: tr!("integrity-files-getting-error"),
-                    description: Some(err.to_string())
-                });
+            else {
+                repair_broken_files(&sender, &progress_bar_input, &report.game_path, report.broken, &cancel);
             }
         }
 
-        sender.input(AppMsg::SetDownloading(false));
+        else {
+            sender.input(AppMsg::Notify {
+                event: NotificationEvent::Repair,
+                title: tr!("integrity-files-getting-error"),
+                description: None,
+                success: false
+            });
+        }
+
+        sender.input(AppMsg::SetBusy(false));
+    });
+}
+
+/// Verify game files without repairing them and show the report
+#[allow(unused_must_use)]
+pub fn verify_game(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>, force_full: bool, cancel: CancellationToken) {
+    progress_bar_input.send(ProgressBarMsg::UpdateCaption(Some(tr!("verifying-files"))));
+    sender.input(AppMsg::SetBusy(true));
+
+    std::thread::spawn(move || {
+        if let Some(report) = verify_files(&sender, &progress_bar_input, force_full, &cancel) {
+            sender.input(AppMsg::ShowVerifyReport(report));
+        }
+
+        sender.input(AppMsg::SetBusy(false));
+    });
+}
+
+/// Repair files chosen in the verification report
+#[allow(unused_must_use)]
+pub fn repair_files(sender: ComponentSender<App>, progress_bar_input: Sender<ProgressBarMsg>, game_path: PathBuf, files: Vec<BrokenFile>, cancel: CancellationToken) {
+    sender.input(AppMsg::SetBusy(true));
+
+    std::thread::spawn(move || {
+        repair_broken_files(&sender, &progress_bar_input, &game_path, files, &cancel);
+
+        sender.input(AppMsg::SetBusy(false));
     });
 }
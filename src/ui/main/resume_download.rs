@@ -0,0 +1,80 @@
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use anime_launcher_sdk::zzz::states::LauncherState;
+
+use crate::*;
+use crate::journal::{self, DownloadJournal, DownloadKind};
+
+use super::{App, AppMsg, MAIN_WINDOW};
+
+/// Ask user to resume the download interrupted by the launcher closing
+///
+/// Does nothing if the journal has no interrupted download for the current launcher state
+pub fn offer_resume(sender: &ComponentSender<App>, state: &LauncherState) {
+    let (kind, version) = match state {
+        LauncherState::GameUpdateAvailable(diff) |
+        LauncherState::GameNotInstalled(diff) |
+        LauncherState::VoiceUpdateAvailable(diff) |
+        LauncherState::VoiceNotInstalled(diff) => (DownloadKind::Update, diff.latest().to_string()),
+
+        LauncherState::PredownloadAvailable { game } => (DownloadKind::Predownload, game.latest().to_string()),
+
+        _ => return
+    };
+
+    let journal = DownloadJournal::load();
+
+    let Some(entry) = journal.interrupted(kind, &version) else {
+        return;
+    };
+
+    tracing::info!("Found interrupted download: {}", entry.target.to_string_lossy());
+
+    let description = if entry.total > 0 {
+        tr!("resume-download-progress", {
+            "version" = version.clone(),
+            "downloaded" = prettify_bytes(entry.offset),
+            "total" = prettify_bytes(entry.total)
+        })
+    } else {
+        tr!("resume-download-description", {
+            "version" = version.clone()
+        })
+    };
+
+    let dialog = adw::MessageDialog::new(
+        unsafe { MAIN_WINDOW.as_ref() },
+        Some(&tr!("resume-download")),
+        Some(&description)
+    );
+
+    dialog.add_response("discard", &tr!("discard"));
+    dialog.add_response("resume", &tr!("resume"));
+
+    dialog.set_response_appearance("discard", adw::ResponseAppearance::Destructive);
+    dialog.set_response_appearance("resume", adw::ResponseAppearance::Suggested);
+
+    dialog.set_default_response(Some("resume"));
+
+    let sender = sender.clone();
+
+    dialog.connect_response(None, move |_, response| {
+        match response {
+            "resume" => match kind {
+                DownloadKind::Update      => sender.input(AppMsg::PerformAction),
+                DownloadKind::Predownload => sender.input(AppMsg::PredownloadUpdate)
+            }
+
+            "discard" => {
+                tracing::info!("Discarding interrupted download");
+
+                journal::discard(kind);
+            }
+
+            _ => ()
+        }
+    });
+
+    dialog.present();
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use relm4::prelude::*;
+
+use anime_launcher_sdk::components::loader::ComponentsLoader;
+
+use crate::*;
+
+use super::{App, AppMsg};
+
+/// How often the main window checks if it's time to look for updates
+pub const UPDATE_CHECK_TICK: Duration = Duration::from_secs(60);
+
+/// Look for new game version and components index changes in background
+///
+/// Errors are only logged since the check is repeated later anyway.
+/// Launcher state is updated afterwards
+pub fn check_updates(sender: ComponentSender<App>) {
+    std::thread::spawn(move || {
+        tracing::info!("Checking for updates");
+
+        let components = ComponentsLoader::new(&CONFIG.components.path);
+
+        match components.is_sync(&CONFIG.components.servers) {
+            Ok(Some(_)) => (),
+
+            Ok(None) => {
+                for host in &CONFIG.components.servers {
+                    match bandwidth::count_folder(&CONFIG.components.path, || components.sync(host)) {
+                        Ok(changes) => {
+                            sender.input(AppMsg::Toast {
+                                title: tr!("components-index-updated"),
+                                description: if changes.is_empty() {
+                                    None
+                                } else {
+                                    Some(changes.into_iter()
+                                        .map(|line| format!("- {line}"))
+                                        .collect::<Vec<_>>()
+                                        .join("\n"))
+                                }
+                            });
+
+                            break;
+                        }
+
+                        Err(err) => tracing::warn!("Failed to sync components index with {host}: {err}")
+                    }
+                }
+            }
+
+            Err(err) => tracing::warn!("Failed to verify that components index synced: {err}")
+        }
+
+        match GAME.try_get_diff() {
+            Ok(diff) => sender.input(AppMsg::SetGameDiff(Some(diff))),
+            Err(err) => tracing::warn!("Failed to find game diff: {err}")
+        }
+
+        sender.input(AppMsg::UpdateLauncherState {
+            perform_on_download_needed: false,
+            show_status_page: false
+        });
+    });
+}
+
+/// Game update which can be announced to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GameUpdate {
+    predownload: bool,
+    current: String,
+    latest: String
+}
+
+impl GameUpdate {
+    fn from_diff(diff: &VersionDiff) -> Option<Self> {
+        match diff {
+            VersionDiff::Predownload { current, latest, .. } => Some(Self {
+                predownload: true,
+                current: current.to_string(),
+                latest: latest.to_string()
+            }),
+
+            VersionDiff::Diff { current, latest, .. } => Some(Self {
+                predownload: false,
+                current: current.to_string(),
+                latest: latest.to_string()
+            }),
+
+            _ => None
+        }
+    }
+}
+
+/// Get toast title about the game update which appeared since the previous check
+pub fn game_update_found(old: &VersionDiff, new: &VersionDiff) -> Option<String> {
+    let update = new_update(GameUpdate::from_diff(old), GameUpdate::from_diff(new))?;
+
+    let title = if update.predownload {
+        tr!("game-predownload-available", {
+            "old" = update.current,
+            "new" = update.latest
+        })
+    } else {
+        tr!("game-update-available", {
+            "old" = update.current,
+            "new" = update.latest
+        })
+    };
+
+    Some(title)
+}
+
+/// Get the `new` update if it wasn't known at the previous check
+fn new_update(old: Option<GameUpdate>, new: Option<GameUpdate>) -> Option<GameUpdate> {
+    let new = new?;
+
+    match old {
+        Some(old) if old.predownload == new.predownload && old.latest == new.latest => None,
+
+        _ => Some(new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(predownload: bool, current: &str, latest: &str) -> Option<GameUpdate> {
+        Some(GameUpdate {
+            predownload,
+            current: current.to_string(),
+            latest: latest.to_string()
+        })
+    }
+
+    #[test]
+    fn update_appeared() {
+        assert_eq!(new_update(None, update(false, "1.0.0", "1.1.0")), update(false, "1.0.0", "1.1.0"));
+        assert_eq!(new_update(None, update(true, "1.0.0", "1.1.0")), update(true, "1.0.0", "1.1.0"));
+
+        // Predownloaded update was released
+        assert_eq!(new_update(update(true, "1.0.0", "1.1.0"), update(false, "1.0.0", "1.1.0")), update(false, "1.0.0", "1.1.0"));
+
+        // Newer update was released before the previous one was installed
+        assert_eq!(new_update(update(false, "1.0.0", "1.1.0"), update(false, "1.0.0", "1.2.0")), update(false, "1.0.0", "1.2.0"));
+    }
+
+    #[test]
+    fn update_already_known() {
+        assert_eq!(new_update(update(false, "1.0.0", "1.1.0"), update(false, "1.0.0", "1.1.0")), None);
+        assert_eq!(new_update(update(true, "1.0.0", "1.1.0"), update(true, "1.0.0", "1.1.0")), None);
+
+        // Only the installed version was changed
+        assert_eq!(new_update(update(false, "1.0.0", "1.2.0"), update(false, "1.1.0", "1.2.0")), None);
+
+        // No update available
+        assert_eq!(new_update(update(false, "1.0.0", "1.1.0"), None), None);
+        assert_eq!(new_update(None, None), None);
+    }
+}
@@ -0,0 +1,96 @@
+use adw::prelude::*;
+
+use anime_launcher_sdk::zzz::config::schema::launcher::LauncherStyle;
+
+use crate::settings::{Settings, WindowState, ClassicWindowState};
+
+/// Default height of the classic style window. Its width is calculated
+/// from the background picture's aspect ratio
+const CLASSIC_HEIGHT: i32 = 624;
+
+/// Width of the classic style window used when the background picture can't be read
+/// (w = 1280 / 730 * h, where 1280x730 is default background picture resolution)
+const CLASSIC_FALLBACK_WIDTH: i32 = 1094;
+
+/// Get the last window state of the launcher style, or its default state
+///
+/// Width of the classic style window is always calculated from the current background picture
+pub fn get(style: LauncherStyle) -> WindowState {
+    let settings = Settings::get_or_default().window;
+
+    match style {
+        LauncherStyle::Modern => settings.modern.unwrap_or(WindowState {
+            width: 900,
+            height: 600,
+            maximized: false
+        }),
+
+        LauncherStyle::Classic => {
+            let state = settings.classic.unwrap_or(ClassicWindowState {
+                height: CLASSIC_HEIGHT,
+                maximized: false
+            });
+
+            WindowState {
+                width: classic_width(state.height),
+                height: state.height,
+                maximized: state.maximized
+            }
+        }
+    }
+}
+
+/// Get width of the classic style window with the background picture's aspect ratio
+fn classic_width(height: i32) -> i32 {
+    match crate::background::processed_background_size() {
+        Ok((width, picture_height)) if width > 0 && picture_height > 0 => {
+            (height as f64 * width as f64 / picture_height as f64).round() as i32
+        }
+
+        Ok(_) => CLASSIC_FALLBACK_WIDTH * height / CLASSIC_HEIGHT,
+
+        Err(err) => {
+            tracing::warn!("Failed to get background picture size: {err}");
+
+            CLASSIC_FALLBACK_WIDTH * height / CLASSIC_HEIGHT
+        }
+    }
+}
+
+/// Apply the last window state of the launcher style to the window
+pub fn restore(window: &adw::ApplicationWindow, style: LauncherStyle) {
+    let state = get(style);
+
+    window.set_default_size(state.width, state.height);
+
+    if state.maximized {
+        window.maximize();
+    } else {
+        window.unmaximize();
+    }
+}
+
+/// Remember current window state for the launcher style
+///
+/// Only height is remembered for the classic style
+pub fn save(window: &adw::ApplicationWindow, style: LauncherStyle) -> anyhow::Result<()> {
+    // Default size is updated by GTK when the window is resized,
+    // and is kept unchanged when it's maximized
+    let (width, height) = window.default_size();
+    let maximized = window.is_maximized();
+
+    Settings::update(|settings| match style {
+        LauncherStyle::Modern => settings.window.modern = Some(WindowState {
+            width,
+            height,
+            maximized
+        }),
+
+        LauncherStyle::Classic => settings.window.classic = Some(ClassicWindowState {
+            height,
+            maximized
+        })
+    })?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 pub mod main;
 pub mod about;
+pub mod verify_report;
 pub mod preferences;
 pub mod components;
 pub mod first_run;
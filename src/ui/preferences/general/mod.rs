@@ -17,6 +17,7 @@ pub mod components;
 use components::*;
 
 use crate::i18n::*;
+use crate::settings::{Settings, BackgroundSettings, BackgroundSource, BackgroundFocalPoint, OfficialBackground, DownloadSettings, NotificationSettings, NotificationEvent};
 use crate::*;
 
 use super::main::PreferencesAppMsg;
@@ -26,7 +27,15 @@ pub struct GeneralApp {
 
     game_diff: Option<VersionDiff>,
     style: LauncherStyle,
-    languages: Vec<String>
+    background: BackgroundSettings,
+    downloads: DownloadSettings,
+    notifications: NotificationSettings,
+    official_backgrounds: Vec<crate::background::Background>,
+    official_backgrounds_list: gtk::StringList,
+    languages: Vec<String>,
+
+    /// Hash all the game files when repairing or verifying them
+    force_full_check: bool
 }
 
 #[derive(Debug, Clone)]
@@ -39,12 +48,43 @@ pub enum GeneralAppMsg {
     UpdateDownloadedDxvk,
 
     RepairGame,
+    VerifyGame,
+    SetForceFullCheck(bool),
 
     OpenMainPage,
     OpenComponentsPage,
 
     UpdateLauncherStyle(LauncherStyle),
 
+    SetBackgroundSource(BackgroundSource),
+    SetOfficialBackgrounds(Vec<crate::background::Background>),
+    SetOfficialBackground(u32),
+    SetBackgroundUrl(String),
+    ChooseBackgroundFile,
+    ChooseBackgroundFolder,
+    SetBackgroundBlur(u32),
+    SetBackgroundDim(u32),
+    SetBackgroundFocalPoint(BackgroundFocalPoint),
+    UpdateBackground,
+    ReprocessBackground,
+
+    /// Download speed limit in KiB/s
+    SetSpeedLimit(u64),
+    SetSpeedLimitSchedule(bool),
+    SetUnlimitedFrom(u32),
+    SetUnlimitedTo(u32),
+    SetMaxJobs(u32),
+    SetRetries(u32),
+
+    /// Delay before the first download retry in seconds
+    SetRetryDelay(u64),
+    SetAutoPredownload(bool),
+
+    /// Update check interval in minutes
+    SetUpdateCheckInterval(u64),
+
+    SetNotificationEnabled(NotificationEvent, bool),
+
     WineOpen(&'static [&'static str]),
 
     Toast {
@@ -132,20 +172,150 @@ impl SimpleAsyncComponent for GeneralApp {
                 #[watch]
                 set_visible: model.style == LauncherStyle::Classic,
 
+                adw::ComboRow {
+                    set_title: &tr!("background-source"),
+                    set_subtitle: &tr!("background-source-description"),
+
+                    set_model: Some(&gtk::StringList::new(&[
+                        &tr!("background-source-official"),
+                        &tr!("background-source-keep"),
+                        &tr!("background-source-file"),
+                        &tr!("background-source-folder"),
+                        &tr!("background-source-url")
+                    ])),
+
+                    set_selected: BackgroundSource::list().iter()
+                        .position(|source| source == &model.background.source)
+                        .unwrap_or(0) as u32,
+
+                    connect_selected_notify[sender] => move |row| {
+                        if is_ready() {
+                            if let Some(source) = BackgroundSource::list().get(row.selected() as usize) {
+                                sender.input(GeneralAppMsg::SetBackgroundSource(*source));
+                            }
+                        }
+                    }
+                },
+
+                adw::ComboRow {
+                    set_title: &tr!("background-official"),
+                    set_subtitle: &tr!("background-official-description"),
+
+                    #[watch]
+                    set_visible: model.background.source == BackgroundSource::Official,
+
+                    set_model: Some(&model.official_backgrounds_list),
+
+                    #[watch]
+                    set_selected: model.official_background_position(),
+
+                    connect_selected_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetOfficialBackground(row.selected()));
+                        }
+                    }
+                },
+
                 adw::ActionRow {
-                    set_title: &tr!("update-background"),
-                    set_subtitle: &tr!("update-background-description"),
+                    set_title: &tr!("background-file"),
 
-                    add_suffix = &gtk::Switch {
-                        set_valign: gtk::Align::Center,
-                        set_active: !KEEP_BACKGROUND_FILE.exists(),
+                    #[watch]
+                    set_subtitle: &model.background.file.as_ref()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_default(),
 
-                        connect_state_notify => |switch| {
-                            #[allow(unused_must_use)]
-                            if switch.is_active() {
-                                std::fs::remove_file(KEEP_BACKGROUND_FILE.as_path());
-                            } else {
-                                std::fs::write(KEEP_BACKGROUND_FILE.as_path(), "");
+                    #[watch]
+                    set_visible: model.background.source == BackgroundSource::File,
+
+                    add_suffix = &gtk::Image {
+                        set_icon_name: Some("document-open-symbolic")
+                    },
+
+                    set_activatable: true,
+
+                    connect_activated => GeneralAppMsg::ChooseBackgroundFile
+                },
+
+                adw::ActionRow {
+                    set_title: &tr!("background-folder"),
+
+                    #[watch]
+                    set_subtitle: &model.background.folder.as_ref()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_else(|| tr!("background-folder-description")),
+
+                    #[watch]
+                    set_visible: model.background.source == BackgroundSource::Folder,
+
+                    add_suffix = &gtk::Image {
+                        set_icon_name: Some("folder-open-symbolic")
+                    },
+
+                    set_activatable: true,
+
+                    connect_activated => GeneralAppMsg::ChooseBackgroundFolder
+                },
+
+                adw::EntryRow {
+                    set_title: &tr!("background-url"),
+                    set_text: &model.background.url,
+
+                    #[watch]
+                    set_visible: model.background.source == BackgroundSource::Url,
+
+                    set_show_apply_button: true,
+
+                    connect_apply[sender] => move |row| {
+                        sender.input(GeneralAppMsg::SetBackgroundUrl(row.text().to_string()));
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("background-blur"),
+                    set_subtitle: &tr!("background-blur-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.background.blur as f64, 0.0, 50.0, 1.0, 5.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetBackgroundBlur(row.value() as u32));
+                        }
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("background-dim"),
+                    set_subtitle: &tr!("background-dim-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.background.dim as f64, 0.0, 90.0, 5.0, 10.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetBackgroundDim(row.value() as u32));
+                        }
+                    }
+                },
+
+                adw::ComboRow {
+                    set_title: &tr!("background-focal-point"),
+                    set_subtitle: &tr!("background-focal-point-description"),
+
+                    set_model: Some(&gtk::StringList::new(&[
+                        &tr!("background-focal-point-center"),
+                        &tr!("background-focal-point-top"),
+                        &tr!("background-focal-point-bottom"),
+                        &tr!("background-focal-point-left"),
+                        &tr!("background-focal-point-right")
+                    ])),
+
+                    set_selected: BackgroundFocalPoint::list().iter()
+                        .position(|point| point == &model.background.focal_point)
+                        .unwrap_or(0) as u32,
+
+                    connect_selected_notify[sender] => move |row| {
+                        if is_ready() {
+                            if let Some(point) = BackgroundFocalPoint::list().get(row.selected() as usize) {
+                                sender.input(GeneralAppMsg::SetBackgroundFocalPoint(*point));
                             }
                         }
                     }
@@ -249,6 +419,22 @@ impl SimpleAsyncComponent for GeneralApp {
                         set_label: &tr!("repair-game"),
 
                         connect_clicked => GeneralAppMsg::RepairGame
+                    },
+
+                    gtk::Button {
+                        set_label: &tr!("verify-game"),
+                        set_tooltip_text: Some(&tr!("verify-game-tooltip")),
+
+                        connect_clicked => GeneralAppMsg::VerifyGame
+                    },
+
+                    gtk::CheckButton {
+                        set_label: Some(&tr!("force-full-check")),
+                        set_tooltip_text: Some(&tr!("force-full-check-tooltip")),
+
+                        connect_toggled[sender] => move |check| {
+                            sender.input(GeneralAppMsg::SetForceFullCheck(check.is_active()));
+                        }
                     }
                 }
             },
@@ -350,6 +536,191 @@ impl SimpleAsyncComponent for GeneralApp {
                 }
             },
 
+            add = &adw::PreferencesGroup {
+                set_title: &tr!("downloads"),
+
+                adw::SpinRow {
+                    set_title: &tr!("speed-limit"),
+                    set_subtitle: &tr!("speed-limit-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.downloads.speed_limit as f64, 0.0, 1048576.0, 128.0, 1024.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetSpeedLimit(row.value() as u64));
+                        }
+                    }
+                },
+
+                adw::ExpanderRow {
+                    set_title: &tr!("speed-limit-schedule"),
+                    set_subtitle: &tr!("speed-limit-schedule-description"),
+
+                    set_show_enable_switch: true,
+                    set_enable_expansion: model.downloads.limit_schedule,
+
+                    connect_enable_expansion_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetSpeedLimitSchedule(row.enables_expansion()));
+                        }
+                    },
+
+                    add_row = &adw::SpinRow {
+                        set_title: &tr!("unlimited-from"),
+
+                        set_adjustment: Some(&gtk::Adjustment::new(model.downloads.unlimited_from as f64, 0.0, 23.0, 1.0, 1.0, 0.0)),
+
+                        connect_value_notify[sender] => move |row| {
+                            if is_ready() {
+                                sender.input(GeneralAppMsg::SetUnlimitedFrom(row.value() as u32));
+                            }
+                        }
+                    },
+
+                    add_row = &adw::SpinRow {
+                        set_title: &tr!("unlimited-to"),
+
+                        set_adjustment: Some(&gtk::Adjustment::new(model.downloads.unlimited_to as f64, 0.0, 23.0, 1.0, 1.0, 0.0)),
+
+                        connect_value_notify[sender] => move |row| {
+                            if is_ready() {
+                                sender.input(GeneralAppMsg::SetUnlimitedTo(row.value() as u32));
+                            }
+                        }
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("max-jobs"),
+                    set_subtitle: &tr!("max-jobs-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.downloads.max_jobs as f64, 1.0, 4.0, 1.0, 1.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetMaxJobs(row.value() as u32));
+                        }
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("download-retries"),
+                    set_subtitle: &tr!("download-retries-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.downloads.retries as f64, 0.0, 10.0, 1.0, 1.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetRetries(row.value() as u32));
+                        }
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("retry-delay"),
+                    set_subtitle: &tr!("retry-delay-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.downloads.retry_delay as f64, 1.0, 60.0, 1.0, 5.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetRetryDelay(row.value() as u64));
+                        }
+                    }
+                },
+
+                adw::SwitchRow {
+                    set_title: &tr!("auto-predownload"),
+                    set_subtitle: &tr!("auto-predownload-description"),
+
+                    set_active: model.downloads.auto_predownload,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetAutoPredownload(switch.is_active()));
+                        }
+                    }
+                },
+
+                adw::SpinRow {
+                    set_title: &tr!("update-check-interval"),
+                    set_subtitle: &tr!("update-check-interval-description"),
+
+                    set_adjustment: Some(&gtk::Adjustment::new(model.downloads.update_check_interval as f64, 0.0, 1440.0, 15.0, 60.0, 0.0)),
+
+                    connect_value_notify[sender] => move |row| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetUpdateCheckInterval(row.value() as u64));
+                        }
+                    }
+                }
+            },
+
+            add = &adw::PreferencesGroup {
+                set_title: &tr!("notifications"),
+                set_description: Some(&tr!("notifications-description")),
+
+                adw::SwitchRow {
+                    set_title: &tr!("notifications-downloads"),
+
+                    set_active: model.notifications.downloads,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetNotificationEnabled(NotificationEvent::Download, switch.is_active()));
+                        }
+                    }
+                },
+
+                adw::SwitchRow {
+                    set_title: &tr!("notifications-predownloads"),
+
+                    set_active: model.notifications.predownloads,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetNotificationEnabled(NotificationEvent::Predownload, switch.is_active()));
+                        }
+                    }
+                },
+
+                adw::SwitchRow {
+                    set_title: &tr!("notifications-repairs"),
+
+                    set_active: model.notifications.repairs,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetNotificationEnabled(NotificationEvent::Repair, switch.is_active()));
+                        }
+                    }
+                },
+
+                adw::SwitchRow {
+                    set_title: &tr!("notifications-wine"),
+
+                    set_active: model.notifications.wine,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetNotificationEnabled(NotificationEvent::Wine, switch.is_active()));
+                        }
+                    }
+                },
+
+                adw::SwitchRow {
+                    set_title: &tr!("notifications-prefix"),
+
+                    set_active: model.notifications.prefix,
+
+                    connect_active_notify[sender] => move |switch| {
+                        if is_ready() {
+                            sender.input(GeneralAppMsg::SetNotificationEnabled(NotificationEvent::Prefix, switch.is_active()));
+                        }
+                    }
+                }
+            },
+
             add = &adw::PreferencesGroup {
                 adw::ActionRow {
                     set_title: &tr!("components"),
@@ -442,13 +813,33 @@ impl SimpleAsyncComponent for GeneralApp {
 
             game_diff: None,
             style: CONFIG.launcher.style,
-            languages: SUPPORTED_LANGUAGES.iter().map(|lang| tr!(format_lang(lang).as_str())).collect()
+            background: Settings::get_or_default().background,
+            downloads: Settings::get_or_default().downloads,
+            notifications: Settings::get_or_default().notifications,
+
+            official_backgrounds: Vec::new(),
+            official_backgrounds_list: gtk::StringList::new(&[
+                &tr!("background-official-latest"),
+                &tr!("background-official-daily")
+            ]),
+
+            languages: SUPPORTED_LANGUAGES.iter().map(|lang| tr!(format_lang(lang).as_str())).collect(),
+
+            force_full_check: false
         };
 
         let components_page = model.components_page.widget();
 
         let widgets = view_output!();
 
+        // List official pictures so one of them can be chosen
+        std::thread::spawn(move || {
+            match crate::background::get_backgrounds_info() {
+                Ok(backgrounds) => sender.input(GeneralAppMsg::SetOfficialBackgrounds(backgrounds)),
+                Err(err) => tracing::error!("Failed to list official background pictures: {err}")
+            }
+        });
+
         AsyncComponentParts { model, widgets }
     }
 
@@ -473,7 +864,19 @@ impl SimpleAsyncComponent for GeneralApp {
             }
 
             GeneralAppMsg::RepairGame => {
-                sender.output(Self::Output::RepairGame).unwrap();
+                sender.output(Self::Output::RepairGame {
+                    force_full: self.force_full_check
+                }).unwrap();
+            }
+
+            GeneralAppMsg::VerifyGame => {
+                sender.output(Self::Output::VerifyGame {
+                    force_full: self.force_full_check
+                }).unwrap();
+            }
+
+            GeneralAppMsg::SetForceFullCheck(state) => {
+                self.force_full_check = state;
             }
 
             GeneralAppMsg::OpenMainPage => unsafe {
@@ -492,7 +895,7 @@ impl SimpleAsyncComponent for GeneralApp {
 
             #[allow(unused_must_use)]
             GeneralAppMsg::UpdateLauncherStyle(style) => {
-                if style == LauncherStyle::Classic && !KEEP_BACKGROUND_FILE.exists() {
+                if style == LauncherStyle::Classic && self.background.source != BackgroundSource::Keep {
                     if let Err(err) = crate::background::download_background() {
                         tracing::error!("Failed to download background picture");
 
@@ -516,6 +919,216 @@ impl SimpleAsyncComponent for GeneralApp {
                 sender.output(Self::Output::SetLauncherStyle(style));
             }
 
+            GeneralAppMsg::SetBackgroundSource(source) => {
+                self.background.source = source;
+
+                self.update_background_settings(&sender, |settings| settings.source = source);
+
+                // Ask for the picture if it wasn't chosen yet
+                match source {
+                    BackgroundSource::File if self.background.file.is_none() => sender.input(GeneralAppMsg::ChooseBackgroundFile),
+                    BackgroundSource::Folder if self.background.folder.is_none() => sender.input(GeneralAppMsg::ChooseBackgroundFolder),
+                    BackgroundSource::Url if self.background.url.is_empty() => (),
+
+                    _ => sender.input(GeneralAppMsg::UpdateBackground)
+                }
+            }
+
+            GeneralAppMsg::SetOfficialBackgrounds(backgrounds) => {
+                let titles = (1..=backgrounds.len())
+                    .map(|number| tr!("background-official-picture", { "number" = number }))
+                    .collect::<Vec<_>>();
+
+                let titles = titles.iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+
+                // Keep "latest" and "daily" items
+                self.official_backgrounds_list.splice(2, self.official_backgrounds_list.n_items() - 2, &titles);
+
+                self.official_backgrounds = backgrounds;
+            }
+
+            GeneralAppMsg::SetOfficialBackground(position) => {
+                let picture = match position {
+                    0 => OfficialBackground::Latest,
+                    1 => OfficialBackground::Daily,
+
+                    position => match self.official_backgrounds.get(position as usize - 2) {
+                        Some(background) => OfficialBackground::Picture(background.hash.clone()),
+                        None => return
+                    }
+                };
+
+                // Selection is also changed when the list is updated
+                if picture == self.background.official {
+                    return;
+                }
+
+                self.background.official = picture.clone();
+
+                self.update_background_settings(&sender, |settings| settings.official = picture);
+
+                sender.input(GeneralAppMsg::UpdateBackground);
+            }
+
+            GeneralAppMsg::SetBackgroundUrl(url) => {
+                self.background.url.clone_from(&url);
+
+                self.update_background_settings(&sender, |settings| settings.url = url);
+
+                sender.input(GeneralAppMsg::UpdateBackground);
+            }
+
+            GeneralAppMsg::ChooseBackgroundFile => {
+                let result = rfd::AsyncFileDialog::new()
+                    .add_filter(tr!("background-file"), crate::background::PICTURE_EXTENSIONS)
+                    .pick_file().await;
+
+                if let Some(result) = result {
+                    let path = result.path().to_path_buf();
+
+                    self.background.file = Some(path.clone());
+
+                    self.update_background_settings(&sender, |settings| settings.file = Some(path));
+
+                    sender.input(GeneralAppMsg::UpdateBackground);
+                }
+            }
+
+            GeneralAppMsg::ChooseBackgroundFolder => {
+                let result = rfd::AsyncFileDialog::new()
+                    .pick_folder().await;
+
+                if let Some(result) = result {
+                    let path = result.path().to_path_buf();
+
+                    self.background.folder = Some(path.clone());
+
+                    self.update_background_settings(&sender, |settings| {
+                        settings.folder = Some(path);
+                        settings.folder_current = None;
+                    });
+
+                    sender.input(GeneralAppMsg::UpdateBackground);
+                }
+            }
+
+            GeneralAppMsg::SetBackgroundBlur(blur) => {
+                self.background.blur = blur;
+
+                self.update_background_settings(&sender, |settings| settings.blur = blur);
+
+                sender.input(GeneralAppMsg::ReprocessBackground);
+            }
+
+            GeneralAppMsg::SetBackgroundDim(dim) => {
+                self.background.dim = dim;
+
+                self.update_background_settings(&sender, |settings| settings.dim = dim);
+
+                sender.input(GeneralAppMsg::ReprocessBackground);
+            }
+
+            #[allow(unused_must_use)]
+            GeneralAppMsg::SetBackgroundFocalPoint(point) => {
+                self.background.focal_point = point;
+
+                self.update_background_settings(&sender, |settings| settings.focal_point = point);
+
+                sender.output(Self::Output::UpdateBackground);
+            }
+
+            #[allow(unused_must_use)]
+            GeneralAppMsg::UpdateBackground => {
+                std::thread::spawn(move || {
+                    match crate::background::download_background() {
+                        Ok(()) => {
+                            sender.output(Self::Output::UpdateBackground);
+                        }
+
+                        Err(err) => {
+                            tracing::error!("Failed to update background picture: {err}");
+
+                            sender.input(GeneralAppMsg::Toast {
+                                title: tr!("background-downloading-failed"),
+                                description: Some(err.to_string())
+                            });
+                        }
+                    }
+                });
+            }
+
+            #[allow(unused_must_use)]
+            GeneralAppMsg::ReprocessBackground => {
+                std::thread::spawn(move || {
+                    match crate::background::reprocess_background() {
+                        Ok(()) => {
+                            sender.output(Self::Output::UpdateBackground);
+                        }
+
+                        Err(err) => {
+                            tracing::error!("Failed to process background picture: {err}");
+
+                            sender.input(GeneralAppMsg::Toast {
+                                title: tr!("background-processing-failed"),
+                                description: Some(err.to_string())
+                            });
+                        }
+                    }
+                });
+            }
+
+            GeneralAppMsg::SetSpeedLimit(limit) => {
+                self.update_download_settings(&sender, |settings| settings.speed_limit = limit);
+            }
+
+            GeneralAppMsg::SetSpeedLimitSchedule(state) => {
+                self.update_download_settings(&sender, |settings| settings.limit_schedule = state);
+            }
+
+            GeneralAppMsg::SetUnlimitedFrom(hour) => {
+                self.update_download_settings(&sender, |settings| settings.unlimited_from = hour);
+            }
+
+            GeneralAppMsg::SetUnlimitedTo(hour) => {
+                self.update_download_settings(&sender, |settings| settings.unlimited_to = hour);
+            }
+
+            GeneralAppMsg::SetMaxJobs(jobs) => {
+                self.update_download_settings(&sender, |settings| settings.max_jobs = jobs);
+
+                // Start queued jobs if more slots are available now
+                crate::queue::schedule();
+            }
+
+            GeneralAppMsg::SetRetries(retries) => {
+                self.update_download_settings(&sender, |settings| settings.retries = retries);
+            }
+
+            GeneralAppMsg::SetRetryDelay(delay) => {
+                self.update_download_settings(&sender, |settings| settings.retry_delay = delay);
+            }
+
+            GeneralAppMsg::SetAutoPredownload(state) => {
+                self.update_download_settings(&sender, |settings| settings.auto_predownload = state);
+            }
+
+            GeneralAppMsg::SetUpdateCheckInterval(interval) => {
+                self.update_download_settings(&sender, |settings| settings.update_check_interval = interval);
+            }
+
+            GeneralAppMsg::SetNotificationEnabled(event, state) => {
+                if let Err(err) = Settings::update(|settings| settings.notifications.set_enabled(event, state)) {
+                    tracing::error!("Failed to update launcher settings: {err}");
+
+                    sender.input(GeneralAppMsg::Toast {
+                        title: tr!("launcher-settings-update-error"),
+                        description: Some(err.to_string())
+                    });
+                }
+            }
+
             GeneralAppMsg::WineOpen(executable) => {
                 let config = Config::get().unwrap_or_else(|_| CONFIG.clone());
 
@@ -547,3 +1160,49 @@ impl SimpleAsyncComponent for GeneralApp {
         }
     }
 }
+
+impl GeneralApp {
+    /// Get position of the chosen official picture in the `official_backgrounds_list`
+    fn official_background_position(&self) -> u32 {
+        match &self.background.official {
+            OfficialBackground::Latest => 0,
+            OfficialBackground::Daily  => 1,
+
+            OfficialBackground::Picture(hash) => self.official_backgrounds.iter()
+                .position(|background| &background.hash == hash)
+                .map(|position| position as u32 + 2)
+                .unwrap_or(gtk::INVALID_LIST_POSITION)
+        }
+    }
+
+    fn update_background_settings(&self, sender: &AsyncComponentSender<Self>, update: impl FnOnce(&mut BackgroundSettings)) {
+        if let Err(err) = Settings::update(|settings| update(&mut settings.background)) {
+            tracing::error!("Failed to update launcher settings: {err}");
+
+            sender.input(GeneralAppMsg::Toast {
+                title: tr!("launcher-settings-update-error"),
+                description: Some(err.to_string())
+            });
+        }
+    }
+
+    /// Update download settings and apply them to the running downloads
+    fn update_download_settings(&mut self, sender: &AsyncComponentSender<Self>, update: impl FnOnce(&mut DownloadSettings)) {
+        match Settings::update(|settings| update(&mut settings.downloads)) {
+            Ok(settings) => {
+                self.downloads = settings.downloads;
+
+                crate::bandwidth::set_settings(settings.downloads);
+            }
+
+            Err(err) => {
+                tracing::error!("Failed to update launcher settings: {err}");
+
+                sender.input(GeneralAppMsg::Toast {
+                    title: tr!("launcher-settings-update-error"),
+                    description: Some(err.to_string())
+                });
+            }
+        }
+    }
+}
@@ -28,8 +28,17 @@ pub enum PreferencesAppMsg {
     SetGameDiff(Option<VersionDiff>),
     SetLauncherStyle(LauncherStyle),
 
+    /// Background picture or its settings were changed
+    UpdateBackground,
+
     UpdateLauncherState,
-    RepairGame,
+    RepairGame {
+        force_full: bool
+    },
+
+    VerifyGame {
+        force_full: bool
+    },
 
     Toast {
         title: String,
@@ -110,6 +119,11 @@ impl SimpleAsyncComponent for PreferencesApp {
                 sender.output(Self::Output::SetLauncherStyle(style));
             }
 
+            #[allow(unused_must_use)]
+            PreferencesAppMsg::UpdateBackground => {
+                sender.output(Self::Output::UpdateBackground);
+            }
+
             #[allow(unused_must_use)]
             PreferencesAppMsg::UpdateLauncherState => {
                 sender.output(Self::Output::UpdateLauncherState {
@@ -119,10 +133,17 @@ impl SimpleAsyncComponent for PreferencesApp {
             }
 
             #[allow(unused_must_use)]
-            PreferencesAppMsg::RepairGame => unsafe {
+            PreferencesAppMsg::RepairGame { force_full } => unsafe {
+                PREFERENCES_WINDOW.as_ref().unwrap_unchecked().close();
+
+                sender.output(Self::Output::RepairGame { force_full });
+            }
+
+            #[allow(unused_must_use)]
+            PreferencesAppMsg::VerifyGame { force_full } => unsafe {
                 PREFERENCES_WINDOW.as_ref().unwrap_unchecked().close();
 
-                sender.output(Self::Output::RepairGame);
+                sender.output(Self::Output::VerifyGame { force_full });
             }
 
             PreferencesAppMsg::Toast { title, description } => unsafe {
@@ -0,0 +1,220 @@
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use crate::*;
+use crate::integrity::{FileIssue, VerifyReport};
+use crate::ui::main::AppMsg;
+
+pub struct VerifyReportDialog {
+    report: Option<VerifyReport>,
+
+    /// Broken files chosen to be repaired
+    selected: Vec<bool>,
+
+    files_group: adw::PreferencesGroup,
+    rows: Vec<adw::ActionRow>,
+
+    visible: bool
+}
+
+#[derive(Debug)]
+pub enum VerifyReportDialogMsg {
+    Show(VerifyReport),
+    Hide,
+
+    SetSelected(usize, bool),
+
+    Export,
+    Repair
+}
+
+#[relm4::component(async, pub)]
+impl SimpleAsyncComponent for VerifyReportDialog {
+    type Init = gtk::Window;
+    type Input = VerifyReportDialogMsg;
+    type Output = AppMsg;
+
+    view! {
+        dialog = adw::Window {
+            set_title: Some(&tr!("verify-report")),
+            set_default_size: (700, 560),
+
+            set_modal: true,
+            set_hide_on_close: true,
+
+            #[watch]
+            set_visible: model.visible,
+
+            #[wrap(Some)]
+            set_content = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    pack_start = &gtk::Button {
+                        set_label: &tr!("export"),
+
+                        connect_clicked => VerifyReportDialogMsg::Export
+                    },
+
+                    pack_end = &gtk::Button {
+                        set_label: &tr!("repair-selected"),
+                        add_css_class: "suggested-action",
+
+                        #[watch]
+                        set_sensitive: model.selected.contains(&true),
+
+                        connect_clicked => VerifyReportDialogMsg::Repair
+                    }
+                },
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &model.files_group,
+                }
+            },
+
+            connect_close_request[sender] => move |_| {
+                sender.input(VerifyReportDialogMsg::Hide);
+
+                gtk::glib::Propagation::Proceed
+            }
+        }
+    }
+
+    async fn init(parent: Self::Init, root: Self::Root, sender: AsyncComponentSender<Self>) -> AsyncComponentParts<Self> {
+        tracing::info!("Initializing verification report dialog");
+
+        let model = Self {
+            report: None,
+            selected: Vec::new(),
+
+            files_group: adw::PreferencesGroup::new(),
+            rows: Vec::new(),
+
+            visible: false
+        };
+
+        let widgets = view_output!();
+
+        widgets.dialog.set_transient_for(Some(&parent));
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(&mut self, msg: Self::Input, sender: AsyncComponentSender<Self>) {
+        match msg {
+            VerifyReportDialogMsg::Show(report) => {
+                for row in self.rows.drain(..) {
+                    self.files_group.remove(&row);
+                }
+
+                self.files_group.set_title(&tr!("verify-report-summary", {
+                    "checked" = report.checked_files,
+                    "broken" = report.broken.len()
+                }));
+
+                let mut description = tr!("verify-report-game-path", {
+                    "path" = report.game_path.to_string_lossy().to_string()
+                });
+
+                if report.cancelled {
+                    description = format!("{description}\n{}", tr!("verify-report-cancelled"));
+                }
+
+                self.files_group.set_description(Some(&description));
+
+                for (i, file) in report.broken.iter().enumerate() {
+                    let row = adw::ActionRow::new();
+
+                    row.set_title(&gtk::glib::markup_escape_text(&file.path.to_string_lossy()));
+
+                    row.set_subtitle(&match file.issue {
+                        FileIssue::Missing => tr!("verify-report-missing"),
+
+                        FileIssue::SizeMismatch => tr!("verify-report-size-mismatch", {
+                            "expected" = file.expected_size,
+                            "actual" = file.actual_size.unwrap_or_default()
+                        }),
+
+                        FileIssue::HashMismatch => tr!("verify-report-hash-mismatch", {
+                            "expected" = file.expected_hash.clone(),
+                            "actual" = file.actual_hash.clone().unwrap_or_else(|| String::from("?"))
+                        })
+                    });
+
+                    let check = gtk::CheckButton::new();
+
+                    check.set_active(true);
+
+                    check.connect_toggled(gtk::glib::clone!(
+                        #[strong]
+                        sender,
+
+                        move |check| sender.input(VerifyReportDialogMsg::SetSelected(i, check.is_active()))
+                    ));
+
+                    row.add_prefix(&check);
+                    row.set_activatable_widget(Some(&check));
+
+                    self.files_group.add(&row);
+                    self.rows.push(row);
+                }
+
+                self.selected = vec![true; report.broken.len()];
+                self.report = Some(report);
+                self.visible = true;
+            }
+
+            VerifyReportDialogMsg::Hide => {
+                self.visible = false;
+            }
+
+            VerifyReportDialogMsg::SetSelected(i, selected) => {
+                if let Some(value) = self.selected.get_mut(i) {
+                    *value = selected;
+                }
+            }
+
+            #[allow(unused_must_use)]
+            VerifyReportDialogMsg::Export => {
+                let Some(report) = &self.report else {
+                    return;
+                };
+
+                let result = rfd::AsyncFileDialog::new()
+                    .set_file_name("verify-report.json")
+                    .add_filter("JSON", &["json"])
+                    .save_file().await;
+
+                if let Some(result) = result {
+                    if let Err(err) = report.export(result.path()) {
+                        tracing::error!("Failed to export verification report: {err}");
+
+                        sender.output(AppMsg::Toast {
+                            title: tr!("verify-report-export-error"),
+                            description: Some(err.to_string())
+                        });
+                    }
+                }
+            }
+
+            #[allow(unused_must_use)]
+            VerifyReportDialogMsg::Repair => {
+                let Some(report) = &self.report else {
+                    return;
+                };
+
+                let files = report.broken.iter()
+                    .zip(&self.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(file, _)| file.clone())
+                    .collect::<Vec<_>>();
+
+                sender.output(AppMsg::RepairFiles {
+                    game_path: report.game_path.clone(),
+                    files
+                });
+
+                self.visible = false;
+            }
+        }
+    }
+}